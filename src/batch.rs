@@ -0,0 +1,428 @@
+use crate::components::Organism;
+use crate::resources::*;
+use crate::sim::SimPlugin;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/**How parameter combinations are picked*/
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub enum Search {
+    /**Every combination of the listed values*/
+    #[default]
+    Grid,
+    /**Independent draws of every parameter*/
+    Random { samples: usize, seed: u64 },
+}
+
+/**Values a swept parameter takes*/
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Axis {
+    Values(Vec<serde_json::Value>),
+    /**Uniformly drawn from [min, max), Random search only*/
+    Range(f64, f64),
+}
+
+/**Batch of headless runs over parameter combinations*/
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sweep {
+    /**Parameters the overrides are applied to*/
+    pub config: String,
+    /**Directory receiving a subdirectory per run and the index*/
+    pub output: String,
+    /**Epochs simulated per run unless the population dies out first*/
+    pub epochs: usize,
+    /**Seeds of the replicates, every combination is run once per seed*/
+    pub seeds: Vec<u64>,
+    pub search: Search,
+    /**Axis of every swept parameter by dotted path, e.g. `food.0.energy`*/
+    pub parameters: BTreeMap<String, Axis>,
+    /**Runs in parallel, None for one per core*/
+    pub threads: Option<usize>,
+}
+
+impl Default for Sweep {
+    fn default() -> Self {
+        Self {
+            config: CONFIG_PATH.to_string(),
+            output: "runs".to_string(),
+            epochs: 50,
+            seeds: vec![0],
+            search: Search::Grid,
+            parameters: BTreeMap::new(),
+            threads: None,
+        }
+    }
+}
+
+/**Parameter values of a single run*/
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RunSpec {
+    pub run: usize,
+    pub seed: u64,
+    pub overrides: BTreeMap<String, serde_json::Value>,
+}
+
+/**Final state of a run*/
+#[derive(Clone, Debug, Serialize)]
+pub struct RunSummary {
+    #[serde(flatten)]
+    pub spec: RunSpec,
+    pub epochs: usize,
+    pub ticks: usize,
+    pub population: usize,
+    pub n_species: usize,
+    /**Species that ever lived*/
+    pub n_lineages: usize,
+    pub n_extinct: usize,
+    pub seconds: f64,
+}
+
+impl Sweep {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ron::from_str(&data).map_err(|e| e.to_string())
+    }
+
+    /**Parameter values of every run, combinations in order and seeds innermost*/
+    pub fn runs(&self) -> Result<Vec<RunSpec>, String> {
+        let combinations: Vec<BTreeMap<String, serde_json::Value>> = match self.search {
+            Search::Grid => {
+                let mut res = vec![BTreeMap::new()];
+                for (name, axis) in self.parameters.iter() {
+                    let Axis::Values(values) = axis else {
+                        return Err(format!(
+                            "{} is a range, only Random search samples ranges",
+                            name
+                        ));
+                    };
+                    res = res
+                        .into_iter()
+                        .flat_map(|c| {
+                            values.iter().map(move |v| {
+                                let mut c = c.clone();
+                                c.insert(name.clone(), v.clone());
+                                c
+                            })
+                        })
+                        .collect();
+                }
+                res
+            }
+            Search::Random { samples, seed } => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                (0..samples)
+                    .map(|_| {
+                        self.parameters
+                            .iter()
+                            .map(|(name, axis)| {
+                                let value = match axis {
+                                    Axis::Values(values) => {
+                                        values[rng.random_range(0..values.len())].clone()
+                                    }
+                                    Axis::Range(min, max) => {
+                                        serde_json::json!(min + rng.random::<f64>() * (max - min))
+                                    }
+                                };
+                                (name.clone(), value)
+                            })
+                            .collect()
+                    })
+                    .collect()
+            }
+        };
+
+        Ok(combinations
+            .into_iter()
+            .flat_map(|overrides| {
+                self.seeds
+                    .iter()
+                    .map(move |seed| (overrides.clone(), *seed))
+            })
+            .enumerate()
+            .map(|(run, (overrides, seed))| RunSpec {
+                run,
+                seed,
+                overrides,
+            })
+            .collect())
+    }
+}
+
+/**Run the sweep described in the file at path, returns once every run has finished*/
+pub fn run_sweep(path: &str) -> Result<(), String> {
+    let sweep = Sweep::load(path)?;
+    let base = Parameters::load(&sweep.config)?;
+    let runs = sweep.runs()?;
+
+    //catch typos before spending hours on the runs
+    let params = runs
+        .iter()
+        .map(|run| base.with_overrides(run.overrides.iter()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    fs::create_dir_all(&sweep.output).map_err(|e| e.to_string())?;
+    let threads = sweep
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+        .clamp(1, runs.len().max(1));
+    println!("Running {} simulations on {} threads", runs.len(), threads);
+
+    let next = AtomicUsize::new(0);
+    let summaries = Mutex::new(Vec::<RunSummary>::with_capacity(runs.len()));
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= runs.len() {
+                        break;
+                    }
+
+                    match run_one(&runs[i], params[i].clone(), &sweep) {
+                        Ok(summary) => {
+                            println!(
+                                "Run {} finished after {} epochs with {} organisms",
+                                summary.spec.run, summary.epochs, summary.population
+                            );
+                            summaries.lock().unwrap().push(summary);
+                        }
+                        Err(e) => eprintln!("Run {} failed: {}", runs[i].run, e),
+                    }
+                }
+            });
+        }
+    });
+
+    let mut summaries = summaries.into_inner().unwrap();
+    summaries.sort_by_key(|s| s.spec.run);
+    let index = format!("{}/index.csv", sweep.output);
+    fs::write(&index, format_index(&summaries, sweep.parameters.keys()))
+        .map_err(|e| e.to_string())?;
+    println!("Wrote {}", index);
+    Ok(())
+}
+
+/**Simulate one run without a window, logging to its own directory*/
+fn run_one(spec: &RunSpec, mut params: Parameters, sweep: &Sweep) -> Result<RunSummary, String> {
+    let dir = format!("{}/run_{:04}", sweep.output, spec.run);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    params.metrics_log = Some(format!(
+        "{}/metrics.{}",
+        dir,
+        match params.metrics_format {
+            MetricsFormat::Csv => "csv",
+            MetricsFormat::JsonLines => "jsonl",
+        }
+    ));
+    params.genealogy_log = Some(format!("{}/genealogy.csv", dir));
+    params.ledger_log = Some(format!("{}/energy_ledger.csv", dir));
+    params.phylogeny_log = Some(format!("{}/phylogeny", dir));
+    params.snapshot_dir = format!("{}/snapshots", dir);
+    params.server_port = None;
+    params.save(&format!("{}/params.ron", dir))?;
+
+    let start = Instant::now();
+    let mut app = headless_app(params, spec.seed);
+    loop {
+        app.update();
+
+        let world = app.world_mut();
+        let epoch = world.resource::<SimState>().epoch;
+        let population = world.query::<&Organism>().iter(world).len();
+        if epoch >= sweep.epochs || population == 0 {
+            break;
+        }
+    }
+
+    let world = app.world_mut();
+    let sim_state = world.resource::<SimState>();
+    let phylogeny = world.resource::<Phylogeny>();
+    let mut summary = RunSummary {
+        spec: spec.clone(),
+        epochs: sim_state.epoch,
+        ticks: sim_state.tick,
+        population: 0,
+        n_species: world.resource::<Species>().len(),
+        n_lineages: phylogeny.n_species(),
+        n_extinct: phylogeny.n_extinct(),
+        seconds: start.elapsed().as_secs_f64(),
+    };
+    summary.population = world.query::<&Organism>().iter(world).len();
+    world.resource_mut::<Genealogy>().flush();
+
+    let json = serde_json::to_string_pretty(&summary).map_err(|e| e.to_string())?;
+    fs::write(format!("{}/summary.json", dir), json).map_err(|e| e.to_string())?;
+    Ok(summary)
+}
+
+/**App running the simulation without a window from seed, every update advances one tick*/
+pub fn headless_app(params: Parameters, seed: u64) -> App {
+    let tick = Duration::from_secs_f32(params.tick_seconds);
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .init_asset::<Image>()
+        .insert_resource(params)
+        .insert_resource(SimRng::seeded(seed))
+        .add_plugins(SimPlugin);
+
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(tick));
+    app.finish();
+    app.cleanup();
+    app
+}
+
+/**One CSV line per run with its swept values and final state*/
+fn format_index<'a>(summaries: &[RunSummary], names: impl Iterator<Item = &'a String>) -> String {
+    let names: Vec<&String> = names.collect();
+    let mut lines = vec![
+        ["run", "seed"]
+            .into_iter()
+            .chain(names.iter().map(|n| n.as_str()))
+            .chain([
+                "epochs",
+                "ticks",
+                "population",
+                "n_species",
+                "n_lineages",
+                "n_extinct",
+                "seconds",
+            ])
+            .collect::<Vec<_>>()
+            .join(","),
+    ];
+
+    for s in summaries {
+        let values = names.iter().map(|n| match s.spec.overrides.get(*n) {
+            Some(serde_json::Value::String(v)) => v.clone(),
+            Some(v) if v.is_array() || v.is_object() => {
+                format!("\"{}\"", v.to_string().replace('"', "\"\""))
+            }
+            Some(v) => v.to_string(),
+            None => String::new(),
+        });
+
+        lines.push(
+            [s.spec.run.to_string(), s.spec.seed.to_string()]
+                .into_iter()
+                .chain(values)
+                .chain([
+                    s.epochs.to_string(),
+                    s.ticks.to_string(),
+                    s.population.to_string(),
+                    s.n_species.to_string(),
+                    s.n_lineages.to_string(),
+                    s.n_extinct.to_string(),
+                    format!("{:.2}", s.seconds),
+                ])
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runs() {
+        let sweep: Sweep = ron::from_str(
+            r#"(
+                seeds: [1, 2],
+                parameters: {
+                    "mutate_gene_proba": Values([0.01, 0.1]),
+                    "topology": Values(["Walled", "Toroidal"]),
+                },
+            )"#,
+        )
+        .unwrap();
+
+        let runs = sweep.runs().unwrap();
+        assert_eq!(8, runs.len());
+        assert_eq!((7, 2), (runs[7].run, runs[7].seed));
+        assert_eq!(
+            serde_json::json!(0.1),
+            runs[7].overrides["mutate_gene_proba"]
+        );
+        assert_eq!(serde_json::json!("Toroidal"), runs[7].overrides["topology"]);
+        assert!(
+            Parameters::default()
+                .with_overrides(runs[7].overrides.iter())
+                .is_ok()
+        );
+
+        let sweep = Sweep {
+            search: Search::Random {
+                samples: 3,
+                seed: 0,
+            },
+            parameters: BTreeMap::from([("share_energy".to_string(), Axis::Range(0.5, 1.))]),
+            ..sweep
+        };
+        let runs = sweep.runs().unwrap();
+        assert_eq!(6, runs.len());
+        assert_eq!(runs, sweep.runs().unwrap());
+        assert!(
+            runs.iter()
+                .all(|r| (0.5..1.).contains(&r.overrides["share_energy"].as_f64().unwrap()))
+        );
+
+        //ranges can't be enumerated
+        let sweep = Sweep {
+            search: Search::Grid,
+            ..sweep
+        };
+        assert!(sweep.runs().is_err());
+    }
+
+    #[test]
+    fn test_seeded_runs() {
+        let mut params = Parameters {
+            grid_width: 60,
+            grid_height: 60,
+            n_initial_entities: 30,
+            ledger_log: None,
+            genealogy_log: None,
+            phylogeny_log: None,
+            metrics_log: None,
+            ..Parameters::default()
+        };
+        params.food[0].growth = 0.5;
+        let run = |seed| {
+            let mut app = headless_app(params.clone(), seed);
+            //past the first epoch
+            for _ in 0..210 {
+                app.update();
+            }
+            let world = app.world_mut();
+            let mut orgs: Vec<(usize, u32, usize)> = world
+                .query::<&Organism>()
+                .iter(world)
+                .map(|org| (org.id, org.energy.to_bits(), org.genome.n_genes()))
+                .collect();
+            orgs.sort();
+            orgs
+        };
+
+        //the same seed replays the same history
+        let orgs = run(3);
+        assert!(!orgs.is_empty());
+        assert_eq!(orgs, run(3));
+        assert_ne!(orgs, run(4));
+    }
+}
@@ -1,30 +1,36 @@
-mod action;
 mod activation;
+mod body;
 mod cell_type;
 mod conn;
 mod coord;
+mod digestion;
 mod dir;
 mod gene;
 mod genome;
+mod metabolism;
 mod neural_system;
 mod neuron;
 mod ns_shape;
 mod organism;
 mod pellet;
+mod proprioception;
 mod sensory_system;
 pub mod ui;
 
-pub use action::*;
 use activation::*;
+pub use body::*;
 pub use cell_type::*;
 pub use conn::*;
 pub use coord::*;
+pub use digestion::*;
 pub use dir::*;
 pub use gene::*;
 pub use genome::*;
+pub use metabolism::*;
 pub use neural_system::*;
 pub use neuron::*;
 pub use ns_shape::*;
 pub use organism::*;
 pub use pellet::*;
+pub use proprioception::*;
 pub use sensory_system::*;
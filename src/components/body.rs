@@ -0,0 +1,101 @@
+use super::Coord;
+use bevy::prelude::Component;
+
+/**Genome encoded physique of an organism*/
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Body {
+    /**Side of the square of cells occupied, anchored at the organism's coordinate as its bottom left corner*/
+    pub size: usize,
+    /**Cells moved per move action*/
+    pub speed: usize,
+    /**Age in epochs after which the organism may die of old age*/
+    pub lifespan: usize,
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Self {
+            size: 1,
+            speed: 1,
+            lifespan: 15,
+        }
+    }
+}
+
+impl Body {
+    pub const N_GENES: usize = 3;
+    /**Range of lifespans relative to the base lifespan*/
+    pub const MIN_LIFESPAN: f32 = 0.5;
+    pub const MAX_LIFESPAN: f32 = 2.;
+
+    pub fn from_weights(
+        weights: &[f32],
+        max_size: usize,
+        max_speed: usize,
+        lifespan: usize,
+    ) -> Self {
+        let scale = |w: f32, min: f32, max: f32| min + w.clamp(0., 1.) * (max - min);
+        Self {
+            size: scale(weights[0], 1., max_size.max(1) as f32).round() as usize,
+            speed: scale(weights[1], 1., max_speed.max(1) as f32).round() as usize,
+            lifespan: (scale(weights[2], Self::MIN_LIFESPAN, Self::MAX_LIFESPAN) * lifespan as f32)
+                .round()
+                .max(1.) as usize,
+        }
+    }
+
+    /**Number of occupied cells*/
+    #[inline]
+    pub fn n_cells(&self) -> usize {
+        self.size * self.size
+    }
+
+    /**Most energy the body can store*/
+    #[inline]
+    pub fn capacity(&self) -> f32 {
+        self.n_cells() as f32
+    }
+
+    /**Distance from the anchor cell to the centre of the body in cells*/
+    #[inline]
+    pub fn offset(&self) -> f32 {
+        (self.size - 1) as f32 * 0.5
+    }
+
+    /**Cell at the centre of a body anchored at anchor, the one above and right of it for even sizes*/
+    #[inline]
+    pub fn centre(&self, anchor: Coord<isize>) -> Coord<isize> {
+        let offset = (self.size / 2) as isize;
+        anchor + Coord::new(offset, offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body() {
+        let body = Body::from_weights(&[0., 0., 0.], 3, 2, 20);
+        assert_eq!(
+            Body {
+                size: 1,
+                speed: 1,
+                lifespan: 10
+            },
+            body
+        );
+        assert_eq!(0., body.offset());
+        assert_eq!(Coord::new(4, 7), body.centre(Coord::new(4, 7)));
+
+        let body = Body::from_weights(&[1., 1., 1.], 3, 2, 20);
+        assert_eq!((3, 2, 40), (body.size, body.speed, body.lifespan));
+        assert_eq!(9., body.capacity());
+        assert_eq!(1., body.offset());
+        assert_eq!(Coord::new(5, 8), body.centre(Coord::new(4, 7)));
+
+        //sizes beyond the maximum can't be encoded
+        let body = Body::from_weights(&[0.5, 0.5, 0.5], 1, 1, 20);
+        assert_eq!((1, 1, 25), (body.size, body.speed, body.lifespan));
+    }
+}
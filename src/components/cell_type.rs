@@ -1,3 +1,4 @@
+use bevy::prelude::Entity;
 use num_traits::Zero;
 use std::ops::Add;
 
@@ -5,7 +6,21 @@ use std::ops::Add;
 pub enum CellType {
     Empty,
     Impassable,
-    Consumable,
+    /**Cell holding food of the given kind*/
+    Consumable(usize),
+    /**Cell occupied by an organism: entity and species*/
+    Occupied(Entity, usize),
+}
+
+impl CellType {
+    /**Check whether an organism can move into the cell*/
+    #[inline]
+    pub fn is_passable(self) -> bool {
+        match self {
+            Self::Empty | Self::Consumable(_) => true,
+            Self::Impassable | Self::Occupied(..) => false,
+        }
+    }
 }
 
 impl Zero for CellType {
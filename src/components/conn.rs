@@ -115,9 +115,9 @@ mod tests {
         ];
 
         let genes = vec![
-            Gene(0b010_0000000_0000000_000011010000011),
-            Gene(0b010_0000001_0000000_000011010000011),
-            Gene(0b001_0000000_0000000_000011010000011),
+            Gene(0b010_0000000000_0000000_011010000011),
+            Gene(0b010_0000000001_0000000_011010000011),
+            Gene(0b001_0000000000_0000000_011010000011),
         ];
 
         let conns: Vec<Connection> = genes
@@ -129,4 +129,15 @@ mod tests {
             assert_eq!(test_conn.conn_type, conn.conn_type);
         }
     }
+
+    #[test]
+    fn test_wide_input() {
+        //rays vision with several food kinds and internal senses needs more than 128 inputs
+        let ns_shape = NsShape::new(300, 4, 2);
+        let gene = Gene(0b011_0100101011_0000001_011010000011);
+        let conn = Connection::from_gene(gene, &ns_shape);
+        assert_eq!(ConnectionType::InOut, conn.conn_type);
+        assert_eq!(299, conn.in_index);
+        assert_eq!(ns_shape.input + ns_shape.hidden + 1, conn.out_index);
+    }
 }
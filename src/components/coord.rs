@@ -1,7 +1,7 @@
 use bevy::prelude::Component;
 use std::ops::{Add, AddAssign, Sub};
 
-#[derive(Component, Clone, Copy, PartialEq, Debug)]
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct Coord<T> {
     pub x: T,
     pub y: T,
@@ -0,0 +1,54 @@
+use bevy::prelude::Component;
+
+/**Share of each food kind's energy an organism absorbs*/
+#[derive(Component, Clone)]
+pub struct Digestion {
+    efficiency: Vec<f32>,
+}
+
+impl Digestion {
+    /**Full efficiency for every food kind*/
+    pub fn uniform(n_food_kinds: usize) -> Self {
+        Self {
+            efficiency: vec![1.; n_food_kinds],
+        }
+    }
+
+    /**Efficiencies proportional to gene weights, summing up to one so that specialists digest their food better*/
+    pub fn from_weights(weights: Vec<f32>) -> Self {
+        let total = weights.iter().sum::<f32>();
+        if total <= 0. {
+            let n = weights.len() as f32;
+            return Self {
+                efficiency: weights.iter().map(|_| 1. / n).collect(),
+            };
+        }
+
+        Self {
+            efficiency: weights.iter().map(|w| w / total).collect(),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, kind: usize) -> f32 {
+        self.efficiency[kind]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_efficiency() {
+        let digestion = Digestion::from_weights(vec![0.6, 0.2]);
+        assert!((0.75 - digestion.get(0)).abs() < 1e-6);
+        assert!((0.25 - digestion.get(1)).abs() < 1e-6);
+
+        let digestion = Digestion::from_weights(vec![0., 0.]);
+        assert_eq!(0.5, digestion.get(1));
+
+        let digestion = Digestion::uniform(2);
+        assert_eq!(1., digestion.get(1));
+    }
+}
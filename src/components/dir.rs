@@ -62,6 +62,32 @@ impl Dir {
         }
     }
 
+    pub fn from_value(value: Coord<isize>) -> Self {
+        match (value.x, value.y) {
+            (0, 1) => Self::N,
+            (0, -1) => Self::S,
+            (1, 0) => Self::E,
+            (-1, 0) => Self::W,
+            (1, 1) => Self::NE,
+            (-1, 1) => Self::NW,
+            (1, -1) => Self::SE,
+            (-1, -1) => Self::SW,
+            _ => panic!("Unknown value: {:?}", value),
+        }
+    }
+
+    //*Mirror direction along the horizontal and/or vertical axis */
+    pub fn reflect(self, x: bool, y: bool) -> Self {
+        let mut value = self.value();
+        if x {
+            value.x = -value.x;
+        }
+        if y {
+            value.y = -value.y;
+        }
+        Self::from_value(value)
+    }
+
     //*Clockwise rotation */
     pub fn rotate(self) -> Self {
         match self {
@@ -3,11 +3,12 @@ use rand::{
     distr::{Distribution, StandardUniform},
     Rng,
 };
+use serde::Serialize;
 
 /**<b>Gene structure:</b>
- * Connection: 0-s-o-7i-7o-15w, where s - sensor, o - output, i - input index, o - out index, w - weight
+ * Connection: 0-s-o-10i-7o-12w, where s - sensor, o - output, i - input index, o - out index, w - weight
  * Neuron: 11-2a-m-12i-15w, where a - activation type, m - memory neuron, i - neuron index, w - fire threshold or source weight */
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Serialize)]
 pub struct Gene(pub u32);
 
 impl Gene {
@@ -23,11 +24,11 @@ impl Gene {
         ((self.0 >> 31) & 1) as i32 == 1 && ((self.0 >> 30) & 1) as i32 == 1
     }
 
-    /**Get connection weight as f32 from 15-bit weight value*/
+    /**Get connection weight as f32 from 12-bit weight value*/
     #[inline]
     pub fn get_conn_weight(self) -> f32 {
-        const SCALE: f32 = (i16::MAX / 4) as f32;
-        let w = self.get_weight() - 0x4000;
+        const SCALE: f32 = (0x7ff / 2) as f32;
+        let w = self.get_weight() - 0x800;
         w as f32 / SCALE
     }
 
@@ -38,22 +39,26 @@ impl Gene {
         w as f32 / i16::MAX as f32
     }
 
-    /**Get 15-bit weight value*/
+    /**Get 12-bit connection or 15-bit neuron weight value*/
     #[inline]
     pub fn get_weight(self) -> i32 {
-        (self.0 & 0x7fff) as i32
+        if self.is_connection() {
+            (self.0 & 0xfff) as i32
+        } else {
+            (self.0 & 0x7fff) as i32
+        }
     }
 
     /**Get 7-bit output index*/
     #[inline]
     pub fn get_out_index(self) -> usize {
-        ((self.0 >> 15) & 0x7f) as usize
+        ((self.0 >> 12) & 0x7f) as usize
     }
 
-    /**Get 7-bit input index*/
+    /**Get 10-bit input index, wide enough for every sensor input*/
     #[inline]
     pub fn get_in_index(self) -> usize {
-        ((self.0 >> 22) & 0x7f) as usize
+        ((self.0 >> 19) & 0x3ff) as usize
     }
 
     /**Get 1-bit output type: 1 - output, 0 - internal*/
@@ -137,7 +142,7 @@ mod tests {
 
     #[test]
     fn test_connection_gene() {
-        let value = 0b010_1001001_1111010_000011010000011;
+        let value = 0b010_0001001001_1111010_011010000011;
         let gene = Gene(value);
 
         let w = gene.get_weight();
@@ -176,7 +181,7 @@ mod tests {
 
     #[test]
     fn test_flip_bit() {
-        let gene = Gene(0b010_1001001_1111010_000011010000011);
+        let gene = Gene(0b010_0001001001_1111010_011010000011);
 
         let gene_in_type_flipped = gene.flip_bit(30);
         let in_type = gene_in_type_flipped.get_in_type();
@@ -186,9 +191,9 @@ mod tests {
         let out_type = gene_out_type_flipped.get_out_type();
         assert_eq!(out_type, 1);
 
-        let gene_weight_flipped = gene.flip_bit(12);
+        let gene_weight_flipped = gene.flip_bit(11);
         let weight = gene_weight_flipped.get_weight();
-        assert_eq!(weight, 5763);
+        assert_eq!(weight, 3715);
     }
 
     #[test]
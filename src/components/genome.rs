@@ -1,14 +1,15 @@
 use super::Gene;
 use rand::Rng;
+use serde::Serialize;
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize)]
+#[serde(transparent)]
 pub struct Genome {
     genes: Vec<Gene>,
 }
 
 impl Genome {
-    pub fn new(len: usize) -> Self {
-        let mut rng = rand::rng();
+    pub fn new(len: usize, rng: &mut impl Rng) -> Self {
         Self {
             genes: (0..len).map(|_| rng.random::<Gene>()).collect(),
         }
@@ -19,8 +20,7 @@ impl Genome {
         Self { genes }
     }
 
-    pub fn replicate(&self, mut_p: f64, insert_p: f64, delete_p: f64) -> Self {
-        let mut rng = rand::rng();
+    pub fn replicate(&self, mut_p: f64, insert_p: f64, delete_p: f64, rng: &mut impl Rng) -> Self {
         let mut child_genes = self.genes.clone();
         let genome_len = child_genes.len();
 
@@ -74,6 +74,11 @@ impl Genome {
         }
     }
 
+    #[inline]
+    pub fn n_genes(&self) -> usize {
+        self.genes.len()
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &Gene> {
         self.genes.iter()
     }
@@ -86,16 +91,16 @@ mod tests {
     #[test]
     fn test_genome_distance() {
         let genome1 = Genome::from(vec![
-            Gene(0b010_1001001_1111010_000011010000011),
-            Gene(0b011_1001001_1111010_000011010000011),
-            Gene(0b011_1001001_1111110_000011010000011),
+            Gene(0b010_1001001111_1010000_011010000011),
+            Gene(0b011_1001001111_1010000_011010000011),
+            Gene(0b011_1001001111_1110000_011010000011),
         ]);
 
         let genome2 = Genome::from(vec![
-            Gene(0b010_1001001_1111010_000011010000011),
-            Gene(0b011_1001001_1111010_000010010000011),
-            Gene(0b011_1001011_1111110_000011010000011),
-            Gene(0b011_1001111_1111110_000011010010011),
+            Gene(0b010_1001001111_1010000_011010000011),
+            Gene(0b011_1001001111_1010000_010010000011),
+            Gene(0b011_1001011111_1110000_011010000011),
+            Gene(0b011_1001111111_1110000_011010010011),
         ]);
 
         //first genes are equal, second genes differ in weights, third genes are disjoint, fourth genes are excessive
@@ -109,7 +114,7 @@ mod tests {
 
     #[test]
     fn test_set_gene_types() {
-        let mut genome = Genome::new(50);
+        let mut genome = Genome::new(50, &mut rand::rng());
         genome.set_gene_types(30, 20);
 
         assert_eq!(
@@ -0,0 +1,62 @@
+use bevy::prelude::Component;
+use std::collections::BTreeMap;
+
+/**Per tick upkeep of an organism and the energy it has spent so far by cause*/
+#[derive(Component, Clone, Default)]
+pub struct Metabolism {
+    pub brain: f32,
+    pub genome: f32,
+    pub vision: f32,
+    pub body: f32,
+    pub longevity: f32,
+    /**Energy spent so far by cause*/
+    pub spent: BTreeMap<&'static str, f32>,
+}
+
+impl Metabolism {
+    pub fn new(brain: f32, genome: f32, vision: f32, body: f32, longevity: f32) -> Self {
+        Self {
+            brain,
+            genome,
+            vision,
+            body,
+            longevity,
+            spent: BTreeMap::new(),
+        }
+    }
+
+    /**Upkeep paid every tick*/
+    pub fn basal_costs(&self) -> [(&'static str, f32); 5] {
+        [
+            ("brain", self.brain),
+            ("genome", self.genome),
+            ("vision", self.vision),
+            ("body", self.body),
+            ("longevity", self.longevity),
+        ]
+    }
+
+    #[inline]
+    pub fn record(&mut self, cause: &'static str, amount: f32) {
+        *self.spent.entry(cause).or_default() += amount;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_breakdown() {
+        let mut metabolism = Metabolism::new(0.3, 0.2, 0.1, 0., 0.);
+        for (cause, cost) in metabolism.basal_costs() {
+            metabolism.record(cause, cost);
+        }
+        metabolism.record("move", 0.5);
+        metabolism.record("brain", 0.3);
+
+        assert_eq!(Some(&0.6), metabolism.spent.get("brain"));
+        assert_eq!(Some(&0.5), metabolism.spent.get("move"));
+        assert_eq!(Some(&0.1), metabolism.spent.get("vision"));
+    }
+}
@@ -4,6 +4,7 @@ use petgraph::Direction;
 use petgraph::graph::NodeIndex;
 use petgraph::stable_graph::StableGraph;
 use petgraph::visit::{DfsPostOrder, Reversed};
+use rand::Rng;
 use rand::prelude::IndexedRandom;
 use std::collections::{HashMap, HashSet};
 
@@ -19,8 +20,6 @@ pub struct NeuralSystem {
 }
 
 impl NeuralSystem {
-    pub const ENERGY_COST: f32 = 1e-6;
-
     pub fn new(
         neurons: &Vec<(bool, Neuron)>,
         connections: &Vec<Connection>,
@@ -120,22 +119,46 @@ impl NeuralSystem {
         }
     }
 
-    pub fn get_action(&mut self, input: Vec<f32>) -> Action {
-        let mut rng = rand::rng();
+    /**Number of hidden and output neurons left after pruning*/
+    #[inline]
+    pub fn n_neurons(&self) -> usize {
+        self.nn_graph.node_count() - self.ns_shape.input
+    }
+
+    /**Number of hidden neurons left after pruning*/
+    #[inline]
+    pub fn n_hidden(&self) -> usize {
+        self.n_neurons() - self.ns_shape.output
+    }
+
+    /**Number of connections left after pruning*/
+    #[inline]
+    pub fn n_edges(&self) -> usize {
+        self.nn_graph.edge_count()
+    }
 
-        let probas: Vec<_> = self
-            .forward(&input)
+    /**Sample an action index from the first n_actions outputs, remaining outputs are returned as they are*/
+    pub fn get_action(
+        &mut self,
+        input: Vec<f32>,
+        n_actions: usize,
+        rng: &mut impl Rng,
+    ) -> (usize, Vec<f32>) {
+        let mut output = self.forward(&input);
+        let rest = output.split_off(n_actions.min(output.len()));
+
+        let probas: Vec<_> = output
             .iter()
             .enumerate()
             .map(|(i, o)| (i, ((o + 1.0) * 0.5).clamp(0.0, 1.0) + 1e-3))
             .collect();
 
         let action_index = probas
-            .choose_weighted(&mut rng, |(_, p)| *p)
+            .choose_weighted(rng, |(_, p)| *p)
             .unwrap_or(&(0, 0.))
             .0;
 
-        Action::get(action_index)
+        (action_index, rest)
     }
 
     pub fn forward(&mut self, input: &Vec<f32>) -> Vec<f32> {
@@ -267,6 +290,8 @@ mod tests {
         let mut ns = NeuralSystem::new(&neurons, &connections, ns_shape);
 
         assert_eq!(ns.nn_graph.node_count(), 5);
+        assert_eq!(2, ns.n_neurons());
+        assert_eq!(3, ns.n_edges());
         assert_eq!(ns.sources, HashSet::<usize>::from_iter(vec![0, 1, 2]));
 
         let input = vec![0.5, 0.8];
@@ -1,36 +1,68 @@
 use super::*;
 use bevy::prelude::Component;
+use rand::Rng;
 
 const MINIMUM_REPLICATION_ENERGY: f32 = 0.25;
 const REPLICATION_COST: f32 = 0.2;
 
 #[derive(Component, Clone)]
 pub struct Organism {
+    /**Unique over the whole run, assigned by the genealogy*/
+    pub id: usize,
+    pub parent: Option<usize>,
+    /**Tick of birth*/
+    pub birth: usize,
+    pub offspring: usize,
+    /**Energy gathered from food and prey over the lifetime*/
+    pub gathered: f32,
     pub genome: Genome,
     pub species: usize,
     pub age: usize,
     pub energy: f32,
+    /**Most energy the organism can store, set by its body on spawn*/
+    pub max_energy: f32,
 }
 
 impl Organism {
-    pub fn new(energy: f32, genome_len: usize) -> Self {
-        let genome = Genome::new(genome_len);
+    pub fn new(energy: f32, genome_len: usize, rng: &mut impl Rng) -> Self {
+        let genome = Genome::new(genome_len, rng);
         Self {
+            id: 0,
+            parent: None,
+            birth: 0,
+            offspring: 0,
+            gathered: 0.,
             genome,
             species: 0,
             age: 0,
             energy,
+            max_energy: 1.,
         }
     }
 
     #[inline]
-    pub fn replicate(&mut self, mut_p: f64, insert_p: f64, delete_p: f64) -> Self {
+    pub fn replicate(
+        &mut self,
+        id: usize,
+        tick: usize,
+        mut_p: f64,
+        insert_p: f64,
+        delete_p: f64,
+        rng: &mut impl Rng,
+    ) -> Self {
         self.energy -= REPLICATION_COST;
+        self.offspring += 1;
         Self {
-            genome: self.genome.replicate(mut_p, insert_p, delete_p),
+            id,
+            parent: Some(self.id),
+            birth: tick,
+            offspring: 0,
+            gathered: 0.,
+            genome: self.genome.replicate(mut_p, insert_p, delete_p, rng),
             species: self.species,
             age: 0,
             energy: REPLICATION_COST,
+            max_energy: 1.,
         }
     }
 
@@ -44,6 +76,21 @@ impl Organism {
         self.energy -= quantity;
     }
 
+    /**Discard energy beyond the storage capacity, returns the change*/
+    #[inline]
+    pub fn clamp_energy(&mut self) -> f32 {
+        let clamped = self.energy.clamp(-1., self.max_energy);
+        let delta = clamped - self.energy;
+        self.energy = clamped;
+        delta
+    }
+
+    /**Whether the organism has run out of energy, by starvation or predation*/
+    #[inline]
+    pub fn is_dead(&self) -> bool {
+        self.energy.is_sign_negative()
+    }
+
     #[inline]
     pub fn can_replicate(&self) -> bool {
         self.energy > MINIMUM_REPLICATION_ENERGY
@@ -0,0 +1,144 @@
+use super::{Dir, Organism};
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/**Sense of the organism's own state*/
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum InternalSensor {
+    /**Energy relative to the body's capacity*/
+    Energy,
+    /**Age relative to lifespan*/
+    Age,
+    Heading,
+    LastAction,
+    /**Whether the last move was blocked*/
+    Blocked,
+    /**Sine waves with genome encoded periods*/
+    Oscillators(usize),
+}
+
+impl InternalSensor {
+    pub fn n_inputs(&self, n_actions: usize) -> usize {
+        match self {
+            Self::Heading => 4,
+            Self::LastAction => n_actions,
+            Self::Oscillators(n) => *n,
+            _ => 1,
+        }
+    }
+
+    /**Number of neuron genes decoded as sensor parameters*/
+    pub fn n_genes(&self) -> usize {
+        match self {
+            Self::Oscillators(n) => *n,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct Proprioception {
+    sensors: Vec<InternalSensor>,
+    periods: Vec<f32>,
+    n_actions: usize,
+    pub last_action: usize,
+    pub blocked: bool,
+}
+
+impl Proprioception {
+    pub const MIN_PERIOD: f32 = 2.;
+    pub const MAX_PERIOD: f32 = 100.;
+
+    /**Oscillator periods are scaled from gene weights in [0, 1]*/
+    pub fn new(sensors: &[InternalSensor], weights: Vec<f32>, n_actions: usize) -> Self {
+        Self {
+            sensors: sensors.to_vec(),
+            periods: weights
+                .iter()
+                .map(|w| Self::MIN_PERIOD + w.clamp(0., 1.) * (Self::MAX_PERIOD - Self::MIN_PERIOD))
+                .collect(),
+            n_actions,
+            last_action: 0,
+            blocked: false,
+        }
+    }
+
+    pub fn n_inputs(sensors: &[InternalSensor], n_actions: usize) -> usize {
+        sensors.iter().map(|s| s.n_inputs(n_actions)).sum()
+    }
+
+    pub fn process_data(&self, org: &Organism, dir: Dir, lifespan: usize, tick: usize) -> Vec<f32> {
+        let mut res = Vec::<f32>::with_capacity(Self::n_inputs(&self.sensors, self.n_actions));
+        let mut periods = self.periods.iter();
+
+        for sensor in self.sensors.iter() {
+            match sensor {
+                InternalSensor::Energy => res.push((org.energy / org.max_energy).clamp(-1., 1.)),
+                InternalSensor::Age => {
+                    res.push((org.age as f32 / lifespan.max(1) as f32).min(1.) * 2. - 1.)
+                }
+                InternalSensor::Heading => res.extend(dir.to_arr()),
+                InternalSensor::LastAction => {
+                    let mut one_hot = vec![0.; self.n_actions];
+                    one_hot[self.last_action] = 1.;
+                    res.extend(one_hot);
+                }
+                InternalSensor::Blocked => res.push(if self.blocked { 1. } else { 0. }),
+                InternalSensor::Oscillators(n) => {
+                    for period in periods.by_ref().take(*n) {
+                        res.push((TAU * tick as f32 / period).sin());
+                    }
+                }
+            }
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proprioception() {
+        let sensors = vec![
+            InternalSensor::Energy,
+            InternalSensor::Age,
+            InternalSensor::Heading,
+            InternalSensor::LastAction,
+            InternalSensor::Blocked,
+            InternalSensor::Oscillators(2),
+        ];
+        let n_actions = 5;
+        let mut proprioception = Proprioception::new(&sensors, vec![0., 1.], n_actions);
+        proprioception.last_action = 3;
+        proprioception.blocked = true;
+
+        let mut org = Organism::new(0.5, 1, &mut rand::rng());
+        org.age = 5;
+
+        let res = proprioception.process_data(&org, Dir::NE, 10, 1);
+        assert_eq!(Proprioception::n_inputs(&sensors, n_actions), res.len());
+        assert_eq!([0.5, 0., 1., 0., 1., 0.], res[..6]);
+        assert_eq!(1., res[6 + 3]);
+        assert_eq!(1., res[6 + n_actions]);
+        assert!(res[7 + n_actions].abs() < 1e-6);
+        assert!(((TAU / 100.).sin() - res[8 + n_actions]).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_energy_of_large_body() {
+        let sensors = vec![InternalSensor::Energy];
+        let proprioception = Proprioception::new(&sensors, vec![], 1);
+
+        //a 2x2 body holding 2 of its 4 units of energy is half full
+        let mut org = Organism::new(2., 1, &mut rand::rng());
+        org.max_energy = 4.;
+        assert_eq!(vec![0.5], proprioception.process_data(&org, Dir::N, 10, 1));
+
+        org.energy = -8.;
+        assert_eq!(vec![-1.], proprioception.process_data(&org, Dir::N, 10, 1));
+    }
+}
@@ -1,103 +1,499 @@
-use crate::components::{CellType, Coord, Dir};
+use crate::components::{CellType, Coord, Dir, Genome};
 use crate::resources::*;
-use bevy::prelude::{Component, Vec2};
-use ndarray::{Array1, Array2};
+use bevy::prelude::{Component, Entity, Vec2};
+use ndarray::{Array1, Array3};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/**Perception model used by every organism of a run*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Vision {
+    /**Visible cells projected onto weighted sensor columns*/
+    Columns,
+    /**Distance to and type of the first object hit by every ray*/
+    Rays,
+}
+
+/**Genome encoded shape of the visual field*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Morphology {
+    /**Half angle of the field of view in radians*/
+    pub fov_angle: f32,
+    pub range: usize,
+    /**Number of distinct sensor values per channel, fewer rays give coarser angular resolution*/
+    pub n_rays: usize,
+}
+
+impl Default for Morphology {
+    fn default() -> Self {
+        Self {
+            fov_angle: SensorySystem::FOV_ANGLE.to_radians(),
+            range: SensorySystem::SENSOR_RANGE,
+            n_rays: SensorySystem::N_SENSORS,
+        }
+    }
+}
+
+impl Morphology {
+    pub const N_GENES: usize = 3;
+    const MIN_FOV_ANGLE: f32 = 10.;
+    const MAX_FOV_ANGLE: f32 = 180.;
+
+    /**Decode field of view, range up to max_range and number of rays from gene weights in [0, 1]*/
+    pub fn from_weights(weights: &[f32], max_range: usize) -> Self {
+        let scale = |w: f32, min: f32, max: f32| min + w.clamp(0., 1.) * (max - min);
+        Self {
+            fov_angle: scale(weights[0], Self::MIN_FOV_ANGLE, Self::MAX_FOV_ANGLE).to_radians(),
+            range: scale(weights[1], 1., max_range.max(1) as f32).round() as usize,
+            n_rays: scale(weights[2], 1., SensorySystem::N_SENSORS as f32).round() as usize,
+        }
+    }
+
+    /**Size of the senses relative to the largest possible ones with ranges up to max_range*/
+    pub fn size(&self, max_range: usize) -> f32 {
+        self.fov_angle / Self::MAX_FOV_ANGLE.to_radians()
+            * (self.range as f32 / max_range.max(1) as f32)
+            * (self.n_rays as f32 / SensorySystem::N_SENSORS as f32)
+    }
+}
+
+/**Organism looking around, telling kin from strangers and not seeing its own body*/
+pub struct Observer<'a> {
+    pub entity: Entity,
+    pub species: usize,
+    /**Own genome, genomes of the others and the genetic distance under which they are kin, None compares species*/
+    pub kinship: Option<(&'a Genome, &'a HashMap<Entity, Genome>, f32)>,
+}
+
+impl Observer<'_> {
+    /**Whether a cell shows anything, empty cells and the observer's own body don't*/
+    #[inline]
+    fn sees(&self, cell_type: CellType) -> bool {
+        match cell_type {
+            CellType::Empty => false,
+            CellType::Occupied(e, _) => e != self.entity,
+            _ => true,
+        }
+    }
+
+    fn is_kin(&self, entity: Entity, species: usize) -> bool {
+        match self.kinship {
+            Some((genome, genomes, threshold)) => genomes
+                .get(&entity)
+                .is_some_and(|other| genome.get_distance(other) < threshold),
+            None => species == self.species,
+        }
+    }
+}
 
 #[derive(Component, Clone)]
 pub struct SensorySystem {
     weights: Array1<f32>,
-    fov_angle: f32,
+    pub morphology: Morphology,
+    n_food_kinds: usize,
 }
 
 impl SensorySystem {
     pub const SENSOR_RANGE: usize = 5;
     pub const N_SENSORS: usize = Self::SENSOR_RANGE * 2 + 1;
+    /**Vision channels besides food: walls, kin, strangers*/
+    const N_OBJECT_CHANNELS: usize = 3;
     const FOV_ANGLE: f32 = 46.;
 
-    pub fn new(weights: Vec<f32>) -> Self {
+    pub fn new(weights: Vec<f32>, morphology: Morphology, n_food_kinds: usize) -> Self {
         Self {
             weights: Array1::from_vec(weights),
-            fov_angle: Self::FOV_ANGLE.to_radians(),
+            morphology,
+            n_food_kinds,
         }
     }
 
-    pub fn process_data(&self, grid: &Grid, origin: Coord<isize>, dir: Dir) -> Vec<f32> {
-        let cell_data = grid.get_area(origin, Self::SENSOR_RANGE);
+    /**Number of inputs: one channel per food kind, then walls, kin and strangers*/
+    #[inline]
+    pub fn n_inputs(n_food_kinds: usize) -> usize {
+        Self::N_SENSORS * (n_food_kinds + Self::N_OBJECT_CHANNELS)
+    }
+
+    /**Number of ray inputs: one distance channel, then one type channel per food kind, walls, kin and strangers*/
+    #[inline]
+    pub fn n_ray_inputs(n_food_kinds: usize) -> usize {
+        Self::N_SENSORS * (1 + n_food_kinds + Self::N_OBJECT_CHANNELS)
+    }
+
+    /**Sensor weight and column of the cell at index i of an area of the given radius, cells further
+    than SENSOR_RANGE from the centre share them with their neighbours*/
+    #[inline]
+    fn sensor_index(i: usize, radius: usize) -> usize {
+        let offset = i as f32 - radius as f32;
+        let scale = Self::SENSOR_RANGE as f32 / radius.max(Self::SENSOR_RANGE) as f32;
+        ((offset * scale).round() as isize + Self::SENSOR_RANGE as isize) as usize
+    }
+
+    /**Project visible cells within range onto sensor columns, one block of N_SENSORS values per channel
+    with columns pooled into the first n_rays values*/
+    pub fn process_data(
+        &self,
+        grid: &Grid,
+        origin: Coord<isize>,
+        dir: Dir,
+        observer: &Observer,
+        range: usize,
+    ) -> Vec<f32> {
+        let radius = self.morphology.range.max(1);
+        let cell_data = grid.get_area(origin, radius);
         let dim = cell_data.dim();
-        let mut sensor_data = Array2::<f32>::zeros(dim);
+        let n_channels = self.n_food_kinds + Self::N_OBJECT_CHANNELS;
+        let mut sensor_data = Array3::<f32>::zeros((n_channels, dim.0, dim.1));
         let dir_coord = dir.value();
+        let range = range.min(self.morphology.range);
 
         for x in 0..dim.0 {
             for y in 0..dim.1 {
                 let cell_type = cell_data[[x, y]];
-                if cell_type == CellType::Empty {
+                if !observer.sees(cell_type) {
                     continue;
                 }
 
-                let coord = Coord::<isize>::new(
-                    x as isize - Self::SENSOR_RANGE as isize,
-                    Self::SENSOR_RANGE as isize - y as isize,
-                );
+                let coord =
+                    Coord::<isize>::new(x as isize - radius as isize, radius as isize - y as isize);
+
+                if coord.x.unsigned_abs().max(coord.y.unsigned_abs()) > range {
+                    continue;
+                }
 
                 let angle = Vec2::new(coord.x as f32, coord.y as f32)
                     .angle_to(Vec2::new(dir_coord.x as f32, dir_coord.y as f32));
 
-                if angle.abs() > self.fov_angle {
+                if angle.abs() > self.morphology.fov_angle {
                     continue;
                 }
 
-                sensor_data[[x, y]] = if cell_type == CellType::Consumable {
-                    1.
-                } else {
-                    -1.
-                };
+                sensor_data[[self.channel(cell_type, observer), x, y]] = 1.;
             }
         }
 
+        let n_rays = self.morphology.n_rays.clamp(1, Self::N_SENSORS);
+        let weights =
+            Array1::from_iter((0..dim.0).map(|x| self.weights[Self::sensor_index(x, radius)]));
         sensor_data
-            .t()
-            .dot(&self.weights)
-            .into_iter()
+            .outer_iter()
+            .flat_map(|channel| {
+                let columns = channel.t().dot(&weights);
+                let mut rays = [0.; Self::N_SENSORS];
+                for (i, v) in columns.iter().enumerate() {
+                    rays[Self::sensor_index(i, radius) * n_rays / Self::N_SENSORS] += v;
+                }
+                rays
+            })
             .map(|v| v.tanh())
             .collect()
     }
+
+    /**Cast n_rays rays spread evenly over the field of view, one block of N_SENSORS values per channel.
+    Distances are 1 next to the organism falling to 0 at range, types are one-hot*/
+    pub fn cast_rays(
+        &self,
+        grid: &Grid,
+        origin: Coord<isize>,
+        dir: Dir,
+        observer: &Observer,
+        range: usize,
+    ) -> Vec<f32> {
+        let n_rays = self.morphology.n_rays.clamp(1, Self::N_SENSORS);
+        let range = range.min(self.morphology.range) as f32;
+        let dir_coord = dir.value();
+        let heading = Vec2::new(dir_coord.x as f32, dir_coord.y as f32).normalize();
+        let mut res = vec![0.; Self::n_ray_inputs(self.n_food_kinds)];
+
+        for ray in 0..n_rays {
+            let angle = if n_rays == 1 {
+                0.
+            } else {
+                self.morphology.fov_angle * (2. * ray as f32 / (n_rays - 1) as f32 - 1.)
+            };
+            let ray_dir = Vec2::from_angle(angle).rotate(heading);
+
+            //march in half cell steps
+            for step in 1..=(range * 2.) as usize {
+                let point = ray_dir * step as f32 * 0.5;
+                let offset = Coord::new(point.x.round() as isize, point.y.round() as isize);
+                if offset == Coord::new(0, 0) {
+                    continue;
+                }
+
+                let cell_type = match grid.resolve(origin + offset) {
+                    Some(coord) => grid.get(coord.x as usize, coord.y as usize),
+                    None => CellType::Impassable,
+                };
+                if !observer.sees(cell_type) {
+                    continue;
+                }
+
+                let distance = Vec2::new(offset.x as f32, offset.y as f32).length();
+                res[ray] = (1. - distance / range).max(0.);
+                res[(1 + self.channel(cell_type, observer)) * Self::N_SENSORS + ray] = 1.;
+                break;
+            }
+        }
+
+        res
+    }
+
+    /**Channel of a non-empty cell: food kinds, then walls, kin and strangers*/
+    #[inline]
+    fn channel(&self, cell_type: CellType, observer: &Observer) -> usize {
+        match cell_type {
+            CellType::Consumable(kind) => kind,
+            CellType::Occupied(e, s) if observer.is_kin(e, s) => self.n_food_kinds + 1,
+            CellType::Occupied(..) => self.n_food_kinds + 2,
+            _ => self.n_food_kinds,
+        }
+    }
 }
 
 #[cfg(test)]
 mod grid_tests {
     use super::*;
+    use crate::components::{Body, Gene};
+
+    fn of_species(species: usize) -> Observer<'static> {
+        Observer {
+            entity: Entity::PLACEHOLDER,
+            species,
+            kinship: None,
+        }
+    }
 
     #[test]
     fn test_sensory_system() {
-        let mut grid = Grid::new((100, 100));
+        let mut grid = Grid::new((100, 100), Topology::Walled);
         let origin = Coord::<isize>::new(50, 50);
+        let entity = Entity::from_raw_u32(0).unwrap();
 
         //test north quadrant
         grid.set(46, 45, CellType::Impassable);
-        grid.set(50, 45, CellType::Consumable);
+        grid.set(50, 45, CellType::Consumable(0));
+        grid.set(48, 46, CellType::Occupied(entity, 0));
+        grid.set(52, 46, CellType::Occupied(entity, 1));
 
         //test south quadrant
-        grid.set(45, 55, CellType::Consumable);
-        grid.set(50, 55, CellType::Consumable);
+        grid.set(45, 55, CellType::Consumable(0));
+        grid.set(50, 55, CellType::Consumable(0));
         grid.set(50, 53, CellType::Impassable);
-        grid.set(55, 55, CellType::Consumable);
+        grid.set(55, 55, CellType::Consumable(0));
 
         //test east quadrant
         grid.set(55, 54, CellType::Impassable);
 
         //test values are precalculated manually
-        let ss = SensorySystem::new(vec![0.6, 0.3, 0., 0.8, 1.2, -0.1, 0.5, -1.3, 2.1, 0., 1.4]);
+        let weights = vec![0.6, 0.3, 0., 0.8, 1.2, -0.1, 0.5, -1.3, 2.1, 0., 1.4];
+        let ss = SensorySystem::new(weights.clone(), Morphology::default(), 1);
+        let (own, other) = (of_species(0), of_species(1));
+        let wall = SensorySystem::N_SENSORS;
+        let kin = SensorySystem::N_SENSORS * 2;
+        let stranger = SensorySystem::N_SENSORS * 3;
+
+        let res = ss.process_data(&grid, origin, Dir::N, &own, SensorySystem::SENSOR_RANGE);
+        assert_eq!(SensorySystem::n_inputs(1), res.len());
+        assert_eq!(((-0.1_f32).tanh() * 1e6) as isize, (res[0] * 1e6) as isize);
+        assert_eq!((0.3_f32.tanh() * 1e6) as isize, (res[wall] * 1e6) as isize);
+        assert_eq!((0.8_f32.tanh() * 1e6) as isize, (res[kin + 1] * 1e6) as isize);
+        assert_eq!(((-1.3_f32).tanh() * 1e6) as isize, (res[stranger + 1] * 1e6) as isize);
+
+        let res = ss.process_data(&grid, origin, Dir::N, &other, SensorySystem::SENSOR_RANGE);
+        assert_eq!(((-1.3_f32).tanh() * 1e6) as isize, (res[kin + 1] * 1e6) as isize);
+        assert_eq!((0.8_f32.tanh() * 1e6) as isize, (res[stranger + 1] * 1e6) as isize);
+
+        let res = ss.process_data(&grid, origin, Dir::S, &own, SensorySystem::SENSOR_RANGE);
+        assert_eq!(((-0.1_f32).tanh() * 1e6) as isize, (res[wall + 8] * 1e6) as isize);
+        assert_eq!((1.9_f32.tanh() * 1e6) as isize, (res[10] * 1e6) as isize);
+
+        let res = ss.process_data(&grid, origin, Dir::E, &own, SensorySystem::SENSOR_RANGE);
+        assert_eq!((1.4_f32.tanh() * 1e6) as isize, (res[wall + 9] * 1e6) as isize);
+        assert_eq!((1.4_f32.tanh() * 1e6) as isize, (res[10] * 1e6) as isize);
+
+        //reduced range hides distant cells
+        let res = ss.process_data(&grid, origin, Dir::N, &own, 4);
+        assert_eq!(0., res[0]);
+        assert_eq!((0.8_f32.tanh() * 1e6) as isize, (res[kin + 1] * 1e6) as isize);
+
+        //second food kind gets its own channel
+        grid.set(50, 45, CellType::Consumable(1));
+        let ss = SensorySystem::new(weights.clone(), Morphology::default(), 2);
+
+        let res = ss.process_data(&grid, origin, Dir::N, &own, SensorySystem::SENSOR_RANGE);
+        assert_eq!(SensorySystem::n_inputs(2), res.len());
+        assert_eq!(0., res[0]);
+        assert_eq!(((-0.1_f32).tanh() * 1e6) as isize, (res[wall] * 1e6) as isize);
+        assert_eq!((0.3_f32.tanh() * 1e6) as isize, (res[wall * 2] * 1e6) as isize);
+
+        //narrow field of view and coarse rays
+        let morphology = Morphology {
+            fov_angle: 10_f32.to_radians(),
+            range: 3,
+            n_rays: 2,
+        };
+        let ss = SensorySystem::new(weights, morphology, 2);
+
+        let res = ss.process_data(&grid, origin, Dir::S, &own, SensorySystem::SENSOR_RANGE);
+        assert_eq!(0., res[10]);
+        assert_eq!(((-0.1_f32).tanh() * 1e6) as isize, (res[wall * 2 + 1] * 1e6) as isize);
+        assert_eq!(0., res[wall * 2 + 8]);
+    }
+
+    #[test]
+    fn test_ray_casting() {
+        let mut grid = Grid::new((100, 100), Topology::Walled);
+        let origin = Coord::<isize>::new(50, 50);
+        let entity = Entity::from_raw_u32(0).unwrap();
+        grid.set(50, 53, CellType::Consumable(0));
+        grid.set(48, 52, CellType::Occupied(entity, 0));
+
+        let morphology = Morphology {
+            n_rays: 3,
+            ..Default::default()
+        };
+        let ss = SensorySystem::new(vec![0.; SensorySystem::N_SENSORS], morphology, 1);
+        let own = of_species(0);
+        let n = SensorySystem::N_SENSORS;
+
+        let res = ss.cast_rays(&grid, origin, Dir::N, &own, SensorySystem::SENSOR_RANGE);
+        assert_eq!(SensorySystem::n_ray_inputs(1), res.len());
+
+        //east ray sees nothing
+        assert_eq!(0., res[0]);
+        assert_eq!(0., res.iter().skip(n).step_by(n).sum::<f32>());
+
+        //center ray hits food
+        assert!((0.4 - res[1]).abs() < 1e-6);
+        assert_eq!(1., res[n + 1]);
+
+        //west ray hits kin
+        assert!((1. - 8_f32.sqrt() / 5. - res[2]).abs() < 1e-6);
+        assert_eq!(1., res[3 * n + 2]);
+
+        //world edge is seen as a wall
+        let res = ss.cast_rays(&grid, Coord::new(98, 50), Dir::E, &own, 3);
+        assert!((1. - 2. / 3. - res[1]).abs() < 1e-6);
+        assert_eq!(1., res[2 * n + 1]);
+    }
+
+    #[test]
+    fn test_genetic_kin() {
+        let mut grid = Grid::new((20, 20), Topology::Walled);
+        let origin = Coord::<isize>::new(10, 10);
+        let (relative, cousin) = (
+            Entity::from_raw_u32(1).unwrap(),
+            Entity::from_raw_u32(2).unwrap(),
+        );
+        grid.set(10, 8, CellType::Occupied(relative, 1));
+        grid.set(10, 7, CellType::Occupied(cousin, 0));
+
+        let genome = Genome::from(vec![Gene(0), Gene(0)]);
+        let genomes = HashMap::from([
+            (relative, genome.clone()),
+            (cousin, Genome::from(vec![Gene(0)])),
+        ]);
+        let observer = Observer {
+            entity: Entity::PLACEHOLDER,
+            species: 0,
+            kinship: Some((&genome, &genomes, 0.3)),
+        };
+
+        //a close relative of another species is kin, a distant member of the same species isn't
+        let ss = SensorySystem::new(vec![1.; SensorySystem::N_SENSORS], Morphology::default(), 1);
+        let res = ss.process_data(
+            &grid,
+            origin,
+            Dir::N,
+            &observer,
+            SensorySystem::SENSOR_RANGE,
+        );
+        let (kin, stranger) = (SensorySystem::N_SENSORS * 2, SensorySystem::N_SENSORS * 3);
+        assert_eq!(1_f32.tanh(), res[kin..stranger].iter().sum::<f32>());
+        assert_eq!(1_f32.tanh(), res[stranger..].iter().sum::<f32>());
+    }
+
+    #[test]
+    fn test_own_body() {
+        let mut grid = Grid::new((20, 20), Topology::Walled);
+        let entity = Entity::from_raw_u32(0).unwrap();
+        let body = Body {
+            size: 2,
+            ..Default::default()
+        };
+        let anchor = Coord::<isize>::new(10, 10);
+        grid.set_footprint(anchor, body.size, CellType::Occupied(entity, 0));
+        let observer = Observer {
+            entity,
+            ..of_species(0)
+        };
+        let origin = body.centre(anchor);
+
+        //a body doesn't see its own cells as kin
+        let ss = SensorySystem::new(vec![1.; SensorySystem::N_SENSORS], Morphology::default(), 1);
+        for dir in [Dir::N, Dir::E, Dir::S, Dir::W] {
+            let res = ss.process_data(&grid, origin, dir, &observer, SensorySystem::SENSOR_RANGE);
+            assert_eq!(0., res.iter().sum::<f32>());
+        }
+
+        //nor do rays stop inside it
+        let morphology = Morphology {
+            n_rays: 1,
+            ..Default::default()
+        };
+        let ss = SensorySystem::new(vec![0.; SensorySystem::N_SENSORS], morphology, 1);
+        let range = SensorySystem::SENSOR_RANGE;
+        grid.set(11, 14, CellType::Consumable(0));
+        let res = ss.cast_rays(&grid, origin, Dir::N, &observer, range);
+        assert!((1. - 3. / 5. - res[0]).abs() < 1e-6);
+        assert_eq!(1., res[SensorySystem::N_SENSORS]);
+        let res = ss.cast_rays(&grid, origin, Dir::S, &observer, range);
+        assert_eq!(0., res.iter().sum::<f32>());
+    }
+
+    #[test]
+    fn test_morphology() {
+        let morphology = Morphology::from_weights(&[1., 0., 0.5], 10);
+        assert_eq!(180_f32.to_radians(), morphology.fov_angle);
+        assert_eq!(1, morphology.range);
+        assert_eq!(6, morphology.n_rays);
+        assert!((morphology.size(10) - 6. / 110.).abs() < 1e-6);
+
+        //range can evolve past the default
+        let morphology = Morphology::from_weights(&[0., 1., 1.], 10);
+        assert_eq!(10, morphology.range);
+        assert_eq!(SensorySystem::N_SENSORS, morphology.n_rays);
+    }
+
+    #[test]
+    fn test_long_range() {
+        let mut grid = Grid::new((40, 40), Topology::Walled);
+        let origin = Coord::<isize>::new(20, 20);
+        grid.set(20, 12, CellType::Consumable(0));
+        let observer = of_species(0);
+        let range = 10;
 
-        let res = ss.process_data(&grid, origin, Dir::N);
-        assert_eq!(11, res.len());
-        assert_eq!(((-0.4_f32).tanh() * 1e6) as usize, (res[0] * 1e6) as usize);
+        //food 8 cells north is beyond the default range
+        let ss = SensorySystem::new(vec![1.; SensorySystem::N_SENSORS], Morphology::default(), 1);
+        let res = ss.process_data(&grid, origin, Dir::N, &observer, range);
+        assert_eq!(0., res.iter().sum::<f32>());
 
-        let res = ss.process_data(&grid, origin, Dir::S);
-        assert_eq!((0.1_f32.tanh() * 1e6) as usize, (res[8] * 1e6) as usize);
-        assert_eq!((1.9_f32.tanh() * 1e6) as usize, (res[10] * 1e6) as usize);
+        //a longer range sees it, cells 7 and 8 away sharing a column
+        let morphology = Morphology {
+            range,
+            ..Default::default()
+        };
+        let ss = SensorySystem::new(vec![1.; SensorySystem::N_SENSORS], morphology, 1);
+        let res = ss.process_data(&grid, origin, Dir::N, &observer, range);
+        assert_eq!(SensorySystem::n_inputs(1), res.len());
+        assert_eq!(1_f32.tanh(), res[1]);
+        assert_eq!(1_f32.tanh(), res.iter().sum::<f32>());
 
-        let res = ss.process_data(&grid, origin, Dir::E);
-        assert_eq!(((-1.4_f32).tanh() * 1e6) as usize, (res[9] * 1e6) as usize);
-        assert_eq!((1.4_f32.tanh() * 1e6) as usize, (res[10] * 1e6) as usize);
+        //and so do rays, which add headings to grid coordinates as they are
+        let ss = SensorySystem::new(vec![0.; SensorySystem::N_SENSORS], morphology, 1);
+        let res = ss.cast_rays(&grid, origin, Dir::S, &observer, range);
+        let centre = SensorySystem::N_SENSORS / 2;
+        assert!((1. - 8. / 10. - res[centre]).abs() < 1e-6);
     }
 }
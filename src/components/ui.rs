@@ -1,4 +1,5 @@
-use bevy::prelude::Component;
+use crate::resources::{EpochTime, HeatmapLayer, Parameters, SimTime};
+use bevy::prelude::{Component, Entity};
 
 #[derive(Component)]
 pub struct GameMenu;
@@ -12,8 +13,234 @@ pub struct PopulationText;
 #[derive(Component)]
 pub struct EnergyText;
 
+#[derive(Component)]
+pub struct LedgerText;
+
 #[derive(Component)]
 pub struct TotalSpeciesText;
 
+/**Label of one of the most populous species, holding the species shown*/
+#[derive(Component, Default)]
+pub struct SpeciesText(pub usize);
+
+#[derive(Component)]
+pub struct ScentOverlay;
+
+/**Sprite with one texel per heatmap region showing the selected layer, if any*/
+#[derive(Component, Default)]
+pub struct HeatmapOverlay(pub Option<HeatmapLayer>);
+
+#[derive(Component)]
+pub struct HeatmapText;
+
+/**Camera target: a selected organism, or the largest organism of a species replaced when it dies*/
+#[derive(Component, Default)]
+pub struct Follow {
+    pub organism: Option<Entity>,
+    pub species: Option<usize>,
+}
+
+impl Follow {
+    pub fn clear(&mut self) {
+        self.organism = None;
+        self.species = None;
+    }
+}
+
+/**Texture of the whole grid, clicking it moves the camera*/
 #[derive(Component)]
-pub struct SpeciesText;
+pub struct Minimap;
+
+/**Frame of the area seen by the camera on the minimap*/
+#[derive(Component)]
+pub struct MinimapViewport;
+
+#[derive(Component)]
+pub struct PhylogenyPanel;
+
+#[derive(Component)]
+pub struct PhylogenyText;
+
+#[derive(Component)]
+pub struct ParameterPanel;
+
+/**Parameter adjustable from the parameter panel*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Setting {
+    MutateGene,
+    InsertGene,
+    DeleteGene,
+    Lifespan,
+    SupplyRate,
+    NeuronCost,
+    EdgeCost,
+    GeneCost,
+    BodyCost,
+    VisionCost,
+    MoveCost,
+    TickDuration,
+    SpeciationThreshold,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 13] = [
+        Setting::MutateGene,
+        Setting::InsertGene,
+        Setting::DeleteGene,
+        Setting::Lifespan,
+        Setting::SupplyRate,
+        Setting::NeuronCost,
+        Setting::EdgeCost,
+        Setting::GeneCost,
+        Setting::BodyCost,
+        Setting::VisionCost,
+        Setting::MoveCost,
+        Setting::TickDuration,
+        Setting::SpeciationThreshold,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Setting::MutateGene => "Mutation proba",
+            Setting::InsertGene => "Insertion proba",
+            Setting::DeleteGene => "Deletion proba",
+            Setting::Lifespan => "Lifespan",
+            Setting::SupplyRate => "Food supply rate",
+            Setting::NeuronCost => "Neuron cost",
+            Setting::EdgeCost => "Connection cost",
+            Setting::GeneCost => "Gene cost",
+            Setting::BodyCost => "Body cost",
+            Setting::VisionCost => "Vision cost",
+            Setting::MoveCost => "Move cost",
+            Setting::TickDuration => "Tick seconds",
+            Setting::SpeciationThreshold => "Speciation threshold",
+        }
+    }
+
+    /**Smallest and largest value and whether the slider is logarithmic*/
+    pub fn range(&self) -> (f32, f32, bool) {
+        match self {
+            Setting::MutateGene | Setting::InsertGene | Setting::DeleteGene => (0., 0.5, false),
+            Setting::Lifespan => (1., 100., false),
+            Setting::SupplyRate => (0., 0.2, false),
+            Setting::TickDuration => (0.005, 0.5, true),
+            Setting::SpeciationThreshold => (0.01, 1., false),
+            _ => (1e-6, 1e-2, true),
+        }
+    }
+
+    pub fn get(&self, params: &Parameters, sim_time: &SimTime) -> f32 {
+        match self {
+            Setting::MutateGene => params.mutate_gene_proba as f32,
+            Setting::InsertGene => params.insert_gene_proba as f32,
+            Setting::DeleteGene => params.delete_gene_proba as f32,
+            Setting::Lifespan => params.lifespan as f32,
+            Setting::SupplyRate => params.supply_rate,
+            Setting::NeuronCost => params.neuron_cost,
+            Setting::EdgeCost => params.edge_cost,
+            Setting::GeneCost => params.gene_cost,
+            Setting::BodyCost => params.body_cost,
+            Setting::VisionCost => params.vision_cost,
+            Setting::MoveCost => params.action_cost("move"),
+            Setting::TickDuration => sim_time.timer.duration().as_secs_f32(),
+            Setting::SpeciationThreshold => params.speciation_threshold,
+        }
+    }
+
+    pub fn set(
+        &self,
+        value: f32,
+        params: &mut Parameters,
+        sim_time: &mut SimTime,
+        epoch_time: &mut EpochTime,
+    ) {
+        match self {
+            Setting::MutateGene => params.mutate_gene_proba = value as f64,
+            Setting::InsertGene => params.insert_gene_proba = value as f64,
+            Setting::DeleteGene => params.delete_gene_proba = value as f64,
+            Setting::Lifespan => params.lifespan = value.round() as usize,
+            Setting::SupplyRate => params.supply_rate = value,
+            Setting::NeuronCost => params.neuron_cost = value,
+            Setting::EdgeCost => params.edge_cost = value,
+            Setting::GeneCost => params.gene_cost = value,
+            Setting::BodyCost => params.body_cost = value,
+            Setting::VisionCost => params.vision_cost = value,
+            Setting::MoveCost => {
+                params.action_costs.insert("move".to_string(), value);
+            }
+            Setting::TickDuration => sim_time.set_tick_duration(epoch_time, value),
+            Setting::SpeciationThreshold => params.speciation_threshold = value,
+        }
+    }
+
+    /**Value at a position along the slider between 0 and 1*/
+    pub fn value_at(&self, fraction: f32) -> f32 {
+        let (min, max, log) = self.range();
+        let fraction = fraction.clamp(0., 1.);
+        if log {
+            (min.ln() + fraction * (max.ln() - min.ln())).exp()
+        } else {
+            min + fraction * (max - min)
+        }
+    }
+
+    /**Position of a value along the slider between 0 and 1*/
+    pub fn fraction_of(&self, value: f32) -> f32 {
+        let (min, max, log) = self.range();
+        let fraction = if log {
+            (value.max(min).ln() - min.ln()) / (max.ln() - min.ln())
+        } else {
+            (value - min) / (max - min)
+        };
+        fraction.clamp(0., 1.)
+    }
+}
+
+/**Track of a setting's slider*/
+#[derive(Component)]
+pub struct SettingSlider(pub Setting);
+
+/**Filled part of a setting's slider*/
+#[derive(Component)]
+pub struct SettingFill(pub Setting);
+
+#[derive(Component)]
+pub struct SettingText(pub Setting);
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum PanelButton {
+    /**Restart the simulation with the edited parameters*/
+    Reset,
+    /**Write the edited parameters to the config file*/
+    Save,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::prelude::{Timer, TimerMode};
+
+    #[test]
+    fn test_setting_fraction() {
+        for setting in Setting::ALL {
+            let (min, max, _) = setting.range();
+            assert!((setting.value_at(0.) - min).abs() < 1e-6);
+            assert!((setting.value_at(1.) - max).abs() / max < 1e-4);
+
+            let value = setting.value_at(0.3);
+            assert!((setting.fraction_of(value) - 0.3).abs() < 1e-4);
+        }
+
+        let mut params = Parameters::default();
+        let mut sim_time = SimTime {
+            timer: Timer::from_seconds(0.05, TimerMode::Repeating),
+        };
+        let mut epoch_time = EpochTime {
+            timer: Timer::from_seconds(10., TimerMode::Repeating),
+        };
+        Setting::TickDuration.set(0.1, &mut params, &mut sim_time, &mut epoch_time);
+        assert!((epoch_time.timer.duration().as_secs_f32() - 20.).abs() < 1e-3);
+        Setting::MoveCost.set(1e-3, &mut params, &mut sim_time, &mut epoch_time);
+        assert_eq!(1e-3, Setting::MoveCost.get(&params, &sim_time));
+    }
+}
@@ -1,11 +1,24 @@
 use bevy::prelude::*;
 
+mod batch;
 mod components;
 mod resources;
+mod server;
 mod sim;
 mod systems;
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let [_, command, path] = args.as_slice()
+        && command == "sweep"
+    {
+        if let Err(e) = batch::run_sweep(path) {
+            eprintln!("Sweep failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -14,6 +27,6 @@ fn main() {
             }),
             ..default()
         }))
-        .add_plugins(sim::SimPlugin)
+        .add_plugins((sim::SimPlugin, sim::ViewPlugin))
         .run();
-}
\ No newline at end of file
+}
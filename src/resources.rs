@@ -1,13 +1,39 @@
+mod energy_ledger;
+mod environment;
 mod epoch_time;
+mod food_field;
+mod genealogy;
 mod grid;
+mod heatmaps;
+mod metrics;
+mod palette;
 mod parameters;
+mod phylogeny;
+mod registry;
+mod scent_field;
+mod signals;
+mod sim_rng;
 mod sim_state;
 mod sim_time;
 mod species;
+mod topology;
 
+pub use energy_ledger::*;
+pub use environment::*;
 pub use epoch_time::*;
+pub use food_field::*;
+pub use genealogy::*;
 pub use grid::*;
+pub use heatmaps::*;
+pub use metrics::*;
+pub use palette::*;
 pub use parameters::*;
+pub use phylogeny::*;
+pub use registry::*;
+pub use scent_field::*;
+pub use signals::*;
+pub use sim_rng::*;
 pub use sim_state::*;
 pub use sim_time::*;
 pub use species::*;
+pub use topology::*;
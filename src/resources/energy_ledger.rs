@@ -0,0 +1,87 @@
+use bevy::prelude::Resource;
+use std::collections::BTreeMap;
+
+/**Energy entering (positive) and leaving (negative) the system by cause, audited against the actual total*/
+#[derive(Resource, Default)]
+pub struct EnergyLedger {
+    pending: f64,
+    last_total: Option<f64>,
+    pub epoch: usize,
+    /**Flows of the current epoch*/
+    pub flows: BTreeMap<&'static str, f64>,
+    /**Flows of the last finished epoch*/
+    pub last_flows: BTreeMap<&'static str, f64>,
+    pub n_anomalies: usize,
+}
+
+impl EnergyLedger {
+    /**Largest unexplained change of the total energy attributed to rounding*/
+    pub const TOLERANCE: f64 = 1e-3;
+
+    #[inline]
+    pub fn record(&mut self, cause: &'static str, delta: f32) {
+        self.pending += delta as f64;
+        *self.flows.entry(cause).or_default() += delta as f64;
+    }
+
+    /**Compare the total energy with the previous total plus the flows recorded since,
+    returns the unexplained change if it exceeds the tolerance*/
+    pub fn audit(&mut self, total: f64) -> Option<f64> {
+        let expected = self.last_total.map(|t| t + self.pending);
+        self.last_total = Some(total);
+        self.pending = 0.;
+
+        let discrepancy = total - expected?;
+        if discrepancy.abs() > Self::TOLERANCE {
+            self.n_anomalies += 1;
+            *self.flows.entry("unaccounted").or_default() += discrepancy;
+            return Some(discrepancy);
+        }
+        None
+    }
+
+    /**Start a new epoch, returns the flows of the finished one*/
+    pub fn close_epoch(&mut self, epoch: usize) -> &BTreeMap<&'static str, f64> {
+        self.epoch = epoch;
+        self.last_flows = std::mem::take(&mut self.flows);
+        &self.last_flows
+    }
+
+    pub fn inflow(&self) -> f64 {
+        self.last_flows.values().filter(|v| **v > 0.).sum()
+    }
+
+    pub fn outflow(&self) -> f64 {
+        self.last_flows.values().filter(|v| **v < 0.).sum()
+    }
+
+    /**Forget the previous total so that the next audit starts a new baseline*/
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit() {
+        let mut ledger = EnergyLedger::default();
+        assert_eq!(None, ledger.audit(10.));
+
+        ledger.record("supply", 2.);
+        ledger.record("brain", -0.5);
+        assert_eq!(None, ledger.audit(11.5));
+
+        //energy vanishing without a recorded cause
+        ledger.record("move", -0.5);
+        assert_eq!(Some(-1.), ledger.audit(10.));
+        assert_eq!(1, ledger.n_anomalies);
+
+        ledger.close_epoch(1);
+        assert_eq!(2., ledger.inflow());
+        assert_eq!(-2., ledger.outflow());
+        assert!(ledger.flows.is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/**Scheduled environmental change, periods of seasons, droughts and migrations are in epochs, days in ticks*/
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Cycle {
+    /**Food supply oscillating within 1 ± amplitude*/
+    Season { period: usize, amplitude: f32 },
+    /**No food supply for the last duration epochs of every period*/
+    Drought { period: usize, duration: usize },
+    /**Fertile regions moving east by shift cells every period*/
+    Migration { period: usize, shift: usize },
+    /**Sensor range shrinking down to night_range at midnight*/
+    DayNight { period: usize, night_range: usize },
+}
+
+/**Current state of the scheduled environment*/
+#[derive(Resource, Clone)]
+pub struct Environment {
+    schedule: Vec<Cycle>,
+    /**Sensor range in full daylight*/
+    max_range: usize,
+    pub food_abundance: f32,
+    pub daylight: f32,
+    pub sensor_range: usize,
+    pub fertility_shift: usize,
+}
+
+impl Environment {
+    pub const N_INPUTS: usize = 2;
+
+    pub fn new(schedule: &[Cycle], max_range: usize) -> Self {
+        let mut res = Self {
+            schedule: schedule.to_vec(),
+            max_range,
+            food_abundance: 1.,
+            daylight: 1.,
+            sensor_range: max_range,
+            fertility_shift: 0,
+        };
+        res.update(0, 0);
+        res
+    }
+
+    pub fn update(&mut self, epoch: usize, tick: usize) {
+        self.food_abundance = 1.;
+        self.daylight = 1.;
+        self.sensor_range = self.max_range;
+        self.fertility_shift = 0;
+
+        for cycle in self.schedule.iter() {
+            match *cycle {
+                Cycle::Season { period, amplitude } => {
+                    let phase = (epoch % period.max(1)) as f32 / period.max(1) as f32;
+                    self.food_abundance *= (1. + amplitude * (phase * TAU).sin()).max(0.);
+                }
+                Cycle::Drought { period, duration } => {
+                    if epoch % period.max(1) + duration >= period.max(1) {
+                        self.food_abundance = 0.;
+                    }
+                }
+                Cycle::Migration { period, shift } => {
+                    self.fertility_shift += epoch / period.max(1) * shift;
+                }
+                Cycle::DayNight {
+                    period,
+                    night_range,
+                } => {
+                    let phase = (tick % period.max(1)) as f32 / period.max(1) as f32;
+                    self.daylight = 0.5 + 0.5 * (phase * TAU).cos();
+
+                    let night_range = night_range.min(self.max_range);
+                    self.sensor_range = night_range
+                        + ((self.max_range - night_range) as f32 * self.daylight).round() as usize;
+                }
+            }
+        }
+    }
+
+    /**Environment sensor values: daylight and food abundance*/
+    pub fn inputs(&self) -> [f32; Self::N_INPUTS] {
+        [
+            self.daylight * 2. - 1.,
+            (self.food_abundance - 1.).clamp(-1., 1.),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::SensorySystem;
+
+    #[test]
+    fn test_static_environment() {
+        let env = Environment::new(&[], SensorySystem::SENSOR_RANGE);
+
+        assert_eq!(1., env.food_abundance);
+        assert_eq!(SensorySystem::SENSOR_RANGE, env.sensor_range);
+        assert_eq!([1., 0.], env.inputs());
+    }
+
+    #[test]
+    fn test_season_and_drought() {
+        let mut env = Environment::new(
+            &[
+                Cycle::Season {
+                    period: 4,
+                    amplitude: 0.5,
+                },
+                Cycle::Drought {
+                    period: 10,
+                    duration: 2,
+                },
+            ],
+            SensorySystem::SENSOR_RANGE,
+        );
+
+        env.update(1, 0);
+        assert!((1.5 - env.food_abundance).abs() < 1e-6);
+
+        env.update(3, 0);
+        assert!((0.5 - env.food_abundance).abs() < 1e-6);
+
+        env.update(8, 0);
+        assert_eq!(0., env.food_abundance);
+        assert_eq!(-1., env.inputs()[1]);
+    }
+
+    #[test]
+    fn test_day_night() {
+        let mut env = Environment::new(
+            &[
+                Cycle::DayNight {
+                    period: 100,
+                    night_range: 1,
+                },
+                Cycle::Migration {
+                    period: 5,
+                    shift: 3,
+                },
+            ],
+            SensorySystem::SENSOR_RANGE,
+        );
+
+        env.update(11, 50);
+        assert!(env.daylight.abs() < 1e-6);
+        assert_eq!(1, env.sensor_range);
+        assert_eq!(6, env.fertility_shift);
+
+        env.update(0, 100);
+        assert_eq!(SensorySystem::SENSOR_RANGE, env.sensor_range);
+    }
+}
@@ -0,0 +1,261 @@
+use crate::components::PELLET_ENERGY;
+use bevy::prelude::{Resource, Vec2, warn};
+use ndarray::Array2;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/**Spatial layout of food supply*/
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FoodDistribution {
+    /**Equal fertility everywhere*/
+    Uniform,
+    /**Fertile patches fixed at startup*/
+    Patches { count: usize, radius: f32 },
+    /**Fertile patches drifting by up to speed cells every epoch*/
+    Hotspots { count: usize, radius: f32, speed: f32 },
+    /**Fertility increasing linearly from west to east*/
+    Gradient,
+    /**Fertility read from a text map, one digit 0-9 per cell, first line is the north edge*/
+    Map(String),
+}
+
+/**Consumable kind with its own nutrition and respawn rules*/
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FoodKind {
+    pub name: String,
+    pub energy: f32,
+    /**Share of the food supply respawned as this kind, 0 disables respawning*/
+    pub supply: f32,
+    pub distribution: FoodDistribution,
+    /**Fraction of new pellets growing next to existing ones of the same kind*/
+    pub growth: f32,
+    pub color: (u8, u8, u8),
+}
+
+impl Default for FoodKind {
+    fn default() -> Self {
+        Self {
+            name: "plant".to_string(),
+            energy: PELLET_ENERGY,
+            supply: 1.,
+            distribution: FoodDistribution::Uniform,
+            growth: 0.,
+            color: (0, 128, 0),
+        }
+    }
+}
+
+/**Per-cell fertility of every food kind used to weight pellet placement*/
+#[derive(Resource, Clone)]
+pub struct FoodField {
+    layers: Vec<FertilityLayer>,
+    shift: usize,
+}
+
+impl FoodField {
+    pub fn new(kinds: &[FoodKind], shape: (usize, usize), rng: &mut impl Rng) -> Self {
+        Self {
+            layers: kinds
+                .iter()
+                .map(|kind| FertilityLayer::new(&kind.distribution, shape, rng))
+                .collect(),
+            shift: 0,
+        }
+    }
+
+    /**Advance moving food sources by one epoch*/
+    pub fn update(&mut self, rng: &mut impl Rng) {
+        for layer in self.layers.iter_mut() {
+            layer.update(rng);
+        }
+    }
+
+    /**Move the whole fertility layout east by shift cells*/
+    pub fn set_shift(&mut self, shift: usize) {
+        self.shift = shift;
+    }
+
+    #[inline]
+    pub fn get(&self, kind: usize, x: usize, y: usize) -> f32 {
+        let fertility = &self.layers[kind].fertility;
+        let width = fertility.dim().0;
+        fertility[[(x + width - self.shift % width) % width, y]]
+    }
+}
+
+#[derive(Clone)]
+struct FertilityLayer {
+    distribution: FoodDistribution,
+    fertility: Array2<f32>,
+    centers: Vec<Vec2>,
+}
+
+impl FertilityLayer {
+    fn new(distribution: &FoodDistribution, shape: (usize, usize), rng: &mut impl Rng) -> Self {
+        let centers = match distribution {
+            FoodDistribution::Patches { count, .. } | FoodDistribution::Hotspots { count, .. } => {
+                (0..*count)
+                    .map(|_| {
+                        Vec2::new(
+                            rng.random_range(0.0..shape.0 as f32),
+                            rng.random_range(0.0..shape.1 as f32),
+                        )
+                    })
+                    .collect()
+            }
+            _ => Vec::<Vec2>::new(),
+        };
+
+        let mut res = Self {
+            distribution: distribution.clone(),
+            fertility: Array2::<f32>::ones(shape),
+            centers,
+        };
+
+        match distribution {
+            FoodDistribution::Map(path) => match fs::read_to_string(path) {
+                Ok(map) => res.fertility = parse_map(&map, shape),
+                Err(e) => warn!("Failed to read food map {}: {}", path, e),
+            },
+            _ => res.compute(),
+        }
+
+        res
+    }
+
+    fn update(&mut self, rng: &mut impl Rng) {
+        let FoodDistribution::Hotspots { speed, .. } = self.distribution else {
+            return;
+        };
+
+        let (width, height) = self.fertility.dim();
+
+        for center in self.centers.iter_mut() {
+            let step = Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU)) * speed;
+            *center += step;
+            center.x = center.x.rem_euclid(width as f32);
+            center.y = center.y.rem_euclid(height as f32);
+        }
+
+        self.compute();
+    }
+
+    fn compute(&mut self) {
+        let width = self.fertility.dim().0;
+
+        match self.distribution {
+            FoodDistribution::Uniform | FoodDistribution::Map(_) => {}
+            FoodDistribution::Gradient => {
+                for ((x, _), v) in self.fertility.indexed_iter_mut() {
+                    *v = (x + 1) as f32 / width as f32;
+                }
+            }
+            FoodDistribution::Patches { radius, .. }
+            | FoodDistribution::Hotspots { radius, .. } => {
+                for ((x, y), v) in self.fertility.indexed_iter_mut() {
+                    let cell = Vec2::new(x as f32, y as f32);
+                    *v = self
+                        .centers
+                        .iter()
+                        .map(|c| (-(cell.distance_squared(*c)) / (radius * radius)).exp())
+                        .sum::<f32>()
+                        .min(1.);
+                }
+            }
+        }
+    }
+}
+
+/**Scale a digit map onto the grid using nearest neighbour sampling*/
+fn parse_map(map: &str, shape: (usize, usize)) -> Array2<f32> {
+    let rows: Vec<Vec<f32>> = map
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.trim()
+                .chars()
+                .map(|c| c.to_digit(10).unwrap_or(0) as f32 / 9.)
+                .collect()
+        })
+        .collect();
+
+    let mut res = Array2::<f32>::zeros(shape);
+    if rows.is_empty() {
+        return res;
+    }
+
+    for ((x, y), v) in res.indexed_iter_mut() {
+        let row = &rows[(shape.1 - 1 - y) * rows.len() / shape.1];
+        if row.is_empty() {
+            continue;
+        }
+        *v = row[x * row.len() / shape.0];
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind(distribution: FoodDistribution) -> FoodKind {
+        FoodKind {
+            distribution,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_gradient() {
+        let rng = &mut rand::rng();
+        let field = FoodField::new(&[kind(FoodDistribution::Gradient)], (10, 5), rng);
+
+        assert_eq!(0.1, field.get(0, 0, 3));
+        assert_eq!(1., field.get(0, 9, 0));
+    }
+
+    #[test]
+    fn test_shift() {
+        let mut field = FoodField::new(
+            &[kind(FoodDistribution::Uniform), kind(FoodDistribution::Gradient)],
+            (10, 5),
+            &mut rand::rng(),
+        );
+        field.set_shift(12);
+
+        assert_eq!(1., field.get(0, 2, 3));
+        assert_eq!(0.1, field.get(1, 2, 3));
+        assert_eq!(1., field.get(1, 1, 0));
+    }
+
+    #[test]
+    fn test_patches() {
+        let layer = FertilityLayer::new(
+            &FoodDistribution::Patches {
+                count: 1,
+                radius: 2.,
+            },
+            (50, 50),
+            &mut rand::rng(),
+        );
+        let center = layer.centers[0];
+
+        assert!(layer.fertility[[center.x as usize, center.y as usize]] > 0.5);
+        assert!(layer.fertility.iter().filter(|v| **v > 0.5).count() < 50);
+    }
+
+    #[test]
+    fn test_parse_map() {
+        let map = "90\n09\n";
+        let fertility = parse_map(map, (4, 4));
+
+        //first line is the north edge
+        assert_eq!(1., fertility[[0, 3]]);
+        assert_eq!(0., fertility[[3, 3]]);
+        assert_eq!(0., fertility[[1, 1]]);
+        assert_eq!(1., fertility[[2, 0]]);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::components::Organism;
+use bevy::prelude::{Resource, warn};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/**Organism id allocation and the log of finished lives*/
+#[derive(Resource, Default)]
+pub struct Genealogy {
+    next_id: usize,
+    writer: Option<BufWriter<File>>,
+}
+
+impl Genealogy {
+    pub const HEADER: &str = "id,parent,birth,death,cause,offspring,gathered";
+
+    /**Genealogy streaming records to the CSV file at path, None keeps no log*/
+    pub fn new(path: Option<&str>) -> Self {
+        let writer = path.and_then(|path| match Self::open(path) {
+            Ok(writer) => Some(writer),
+            Err(e) => {
+                warn!("Failed to open genealogy log {}: {}", path, e);
+                None
+            }
+        });
+
+        Self { next_id: 0, writer }
+    }
+
+    fn open(path: &str) -> std::io::Result<BufWriter<File>> {
+        let path = Path::new(path);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", Self::HEADER)?;
+        Ok(writer)
+    }
+
+    #[inline]
+    pub fn next_id(&mut self) -> usize {
+        self.next_id += 1;
+        self.next_id - 1
+    }
+
+    /**Record the life of an organism that has ended at tick*/
+    pub fn record(&mut self, org: &Organism, tick: usize, cause: &str) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+
+        if let Err(e) = writeln!(writer, "{}", Self::format(org, tick, cause)) {
+            warn!("Failed to write genealogy record: {}", e);
+            self.writer = None;
+        }
+    }
+
+    pub fn flush(&mut self) {
+        if let Some(writer) = self.writer.as_mut()
+            && let Err(e) = writer.flush()
+        {
+            warn!("Failed to flush genealogy log: {}", e);
+        }
+    }
+
+    fn format(org: &Organism, tick: usize, cause: &str) -> String {
+        format!(
+            "{},{},{},{},{},{},{:.4}",
+            org.id,
+            org.parent.map(|p| p.to_string()).unwrap_or_default(),
+            org.birth,
+            tick,
+            cause,
+            org.offspring,
+            org.gathered
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        let mut genealogy = Genealogy::default();
+        let mut parent = Organism::new(1., 5, &mut rand::rng());
+        parent.id = genealogy.next_id();
+        parent.gathered = 0.5;
+
+        let child = parent.replicate(genealogy.next_id(), 7, 0., 0., 0., &mut rand::rng());
+        assert_eq!(1, parent.offspring);
+        assert_eq!(
+            "0,,0,9,predation,1,0.5000",
+            Genealogy::format(&parent, 9, "predation")
+        );
+        assert_eq!(
+            "1,0,7,12,starvation,0,0.0000",
+            Genealogy::format(&child, 12, "starvation")
+        );
+    }
+}
@@ -1,20 +1,71 @@
-use crate::components::{CellType, Coord};
+use super::Topology;
+use crate::components::{CellType, Coord, Dir};
 use bevy::prelude::Resource;
 use ndarray::Array2;
 
 #[derive(Resource, Clone)]
 pub struct Grid {
     data: Array2<CellType>,
+    topology: Topology,
 }
 
 impl Grid {
-    pub fn new(shape: (usize, usize)) -> Self {
+    pub fn new(shape: (usize, usize), topology: Topology) -> Self {
         let v = vec![CellType::Empty; shape.0 * shape.1];
         Self {
             data: Array2::<CellType>::from_shape_vec(shape, v).unwrap(),
+            topology,
         }
     }
 
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.data.dim().0
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.data.dim().1
+    }
+
+    /**Map coordinate onto the grid according to topology, None if it lies beyond the edge*/
+    #[inline]
+    pub fn resolve(&self, coord: Coord<isize>) -> Option<Coord<isize>> {
+        let width = self.width() as isize;
+        let height = self.height() as isize;
+
+        if self.topology == Topology::Toroidal {
+            return Some(Coord::new(
+                coord.x.rem_euclid(width),
+                coord.y.rem_euclid(height),
+            ));
+        }
+
+        if coord.x < 0 || coord.y < 0 || coord.x >= width || coord.y >= height {
+            return None;
+        }
+
+        Some(coord)
+    }
+
+    /**Get destination of a single step and the direction after it, None if the move leaves the world*/
+    pub fn step(&self, origin: Coord<isize>, dir: Dir) -> Option<(Coord<isize>, Dir)> {
+        let next_coord = origin + dir.value();
+        if let Some(coord) = self.resolve(next_coord) {
+            return Some((coord, dir));
+        }
+
+        if self.topology != Topology::Reflective {
+            return None;
+        }
+
+        let reflect_x = next_coord.x < 0 || next_coord.x >= self.width() as isize;
+        let reflect_y = next_coord.y < 0 || next_coord.y >= self.height() as isize;
+        let dir = dir.reflect(reflect_x, reflect_y);
+
+        self.resolve(origin + dir.value()).map(|coord| (coord, dir))
+    }
+
     pub fn set(&mut self, x: usize, y: usize, cell_type: CellType) {
         self.data[[x, y]] = cell_type;
     }
@@ -36,13 +87,23 @@ impl Grid {
             .collect::<Vec<Coord<isize>>>()
     }
 
+    /**Get coordinates and kinds of all food cells*/
+    pub fn get_food_coords(&self) -> Vec<(Coord<isize>, usize)> {
+        self.data
+            .indexed_iter()
+            .filter_map(|((x, y), cell_type)| match cell_type {
+                CellType::Consumable(kind) => Some((Coord::new(x as isize, y as isize), *kind)),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn search_area(
         &self,
         origin: Coord<isize>,
         radius: usize,
         cell_type: CellType,
     ) -> Vec<Coord<isize>> {
-        let grid_size = self.data.dim().0;
         let mut neighbors = Vec::<Coord<isize>>::new();
 
         let start = -(radius as isize);
@@ -50,20 +111,14 @@ impl Grid {
 
         for x in start..end {
             for y in start..end {
-                let coord = origin
-                    + Coord::<isize> {
-                        x: x as isize,
-                        y: y as isize,
-                    };
-                if (x == 0 && y == 0)
-                    || coord.x < 0
-                    || coord.y < 0
-                    || coord.x as usize >= grid_size
-                    || coord.y as usize >= grid_size
-                {
+                if x == 0 && y == 0 {
                     continue;
                 }
 
+                let Some(coord) = self.resolve(origin + Coord::<isize> { x, y }) else {
+                    continue;
+                };
+
                 if self.data[[coord.x as usize, coord.y as usize]] == cell_type {
                     neighbors.push(coord);
                 }
@@ -73,33 +128,81 @@ impl Grid {
         neighbors
     }
 
-    pub fn get_area(&self, origin: Coord<isize>, radius: usize) -> Array2<CellType> {
-        let grid_size = self.data.dim().0;
-        let len = radius * 2 + 1;
-        let mut res = Array2::<CellType>::zeros((len, len));
+    /**Cells covered by a square body of the given size anchored at its bottom left corner, None if any lies beyond the edge*/
+    pub fn footprint(&self, origin: Coord<isize>, size: usize) -> Option<Vec<Coord<isize>>> {
+        let mut cells = Vec::<Coord<isize>>::with_capacity(size * size);
+        for x in 0..size as isize {
+            for y in 0..size as isize {
+                cells.push(self.resolve(origin + Coord::<isize> { x, y })?);
+            }
+        }
+
+        Some(cells)
+    }
+
+    /**Set every cell of a body's footprint lying within the world*/
+    pub fn set_footprint(&mut self, origin: Coord<isize>, size: usize, cell_type: CellType) {
+        for x in 0..size as isize {
+            for y in 0..size as isize {
+                if let Some(coord) = self.resolve(origin + Coord::<isize> { x, y }) {
+                    self.data[[coord.x as usize, coord.y as usize]] = cell_type;
+                }
+            }
+        }
+    }
+
+    /**Anchors within radius of origin where a body of the given size covers only empty cells*/
+    pub fn find_space(
+        &self,
+        origin: Coord<isize>,
+        radius: usize,
+        size: usize,
+    ) -> Vec<Coord<isize>> {
+        let mut anchors = Vec::<Coord<isize>>::new();
 
         let start = -(radius as isize);
         let end = radius as isize + 1;
 
         for x in start..end {
             for y in start..end {
-                let coord = origin
-                    + Coord::<isize> {
-                        x: x as isize,
-                        y: y as isize,
-                    };
-                if coord.x < 0
-                    || coord.y < 0
-                    || coord.x as usize >= grid_size
-                    || coord.y as usize >= grid_size
-                {
+                if x == 0 && y == 0 {
                     continue;
                 }
 
+                let Some(anchor) = self.resolve(origin + Coord::<isize> { x, y }) else {
+                    continue;
+                };
+
+                if self.footprint(anchor, size).is_some_and(|cells| {
+                    cells
+                        .iter()
+                        .all(|c| self.data[[c.x as usize, c.y as usize]] == CellType::Empty)
+                }) {
+                    anchors.push(anchor);
+                }
+            }
+        }
+
+        anchors
+    }
+
+    pub fn get_area(&self, origin: Coord<isize>, radius: usize) -> Array2<CellType> {
+        let len = radius * 2 + 1;
+        let mut res = Array2::<CellType>::zeros((len, len));
+
+        let start = -(radius as isize);
+        let end = radius as isize + 1;
+
+        for x in start..end {
+            for y in start..end {
+                //cells beyond the edge are seen as walls
                 res[[
                     (x + radius as isize) as usize,
                     (y + radius as isize) as usize,
-                ]] = self.data[[coord.x as usize, coord.y as usize]];
+                ]] = match self.resolve(origin + Coord::<isize> { x, y }) {
+                    Some(coord) => self.data[[coord.x as usize, coord.y as usize]],
+                    None => CellType::Impassable,
+                };
             }
         }
 
@@ -110,35 +213,129 @@ impl Grid {
 #[cfg(test)]
 mod grid_tests {
     use super::*;
+    use bevy::prelude::Entity;
 
     #[test]
     fn test_search_area() {
-        let grid = Grid::new((100, 100));
+        let grid = Grid::new((100, 100), Topology::Walled);
         let origin = Coord::<isize>::new(10, 10);
         let neighbors = grid.search_area(origin, 1, CellType::Empty);
         assert!(neighbors.len() == 8);
 
-        let grid = Grid::new((100, 100));
+        let grid = Grid::new((100, 100), Topology::Walled);
         let origin = Coord::<isize>::new(0, 0);
         let neighbors = grid.search_area(origin, 1, CellType::Empty);
         assert!(neighbors.len() == 3);
+
+        let grid = Grid::new((100, 50), Topology::Toroidal);
+        let origin = Coord::<isize>::new(0, 49);
+        let neighbors = grid.search_area(origin, 1, CellType::Empty);
+        assert!(neighbors.len() == 8);
+        assert!(neighbors.contains(&Coord::<isize>::new(99, 0)));
+    }
+
+    #[test]
+    fn test_footprint() {
+        let mut grid = Grid::new((10, 10), Topology::Walled);
+        let origin = Coord::<isize>::new(8, 8);
+        assert_eq!(4, grid.footprint(origin, 2).unwrap().len());
+        assert_eq!(None, grid.footprint(origin, 3));
+
+        //a size 1 body fits wherever search_area finds empty cells
+        grid.set_footprint(Coord::new(4, 4), 2, CellType::Impassable);
+        assert_eq!(
+            grid.search_area(Coord::new(3, 3), 1, CellType::Empty),
+            grid.find_space(Coord::new(3, 3), 1, 1)
+        );
+        assert_eq!(CellType::Impassable, grid.get(5, 5));
+
+        //a size 2 body next to the block
+        let anchors = grid.find_space(Coord::new(2, 4), 1, 2);
+        assert!(anchors.contains(&Coord::new(2, 3)));
+        assert!(!anchors.contains(&Coord::new(3, 4)));
+
+        let grid = Grid::new((10, 10), Topology::Toroidal);
+        let cells = grid.footprint(Coord::new(9, 9), 2).unwrap();
+        assert!(cells.contains(&Coord::new(0, 0)));
     }
 
     #[test]
     fn test_get_area() {
-        let mut grid = Grid::new((100, 100));
+        let mut grid = Grid::new((100, 100), Topology::Walled);
         let origin = Coord::<isize>::new(50, 50);
 
-        grid.set(50, 55, CellType::Consumable);
-        grid.set(55, 55, CellType::Consumable);
+        grid.set(50, 55, CellType::Consumable(0));
+        grid.set(55, 55, CellType::Consumable(1));
         grid.set(46, 45, CellType::Impassable);
 
         let area = grid.get_area(origin, 5);
 
         assert_eq!(11 * 11, area.len());
-        assert_eq!(CellType::Consumable, area[[5, 10]]);
-        assert_eq!(CellType::Consumable, area[[10, 10]]);
+        assert_eq!(CellType::Consumable(0), area[[5, 10]]);
+        assert_eq!(CellType::Consumable(1), area[[10, 10]]);
         assert_eq!(CellType::Impassable, area[[1, 0]]);
         assert_eq!(CellType::Empty, area[[3, 2]]);
+
+        let food = grid.get_food_coords();
+        assert_eq!(2, food.len());
+        assert!(food.contains(&(Coord::<isize>::new(55, 55), 1)));
+    }
+
+    #[test]
+    fn test_get_area_edges() {
+        let mut grid = Grid::new((20, 10), Topology::Walled);
+        grid.set(19, 9, CellType::Consumable(0));
+
+        let area = grid.get_area(Coord::<isize>::new(0, 0), 1);
+        assert_eq!(CellType::Impassable, area[[0, 0]]);
+        assert_eq!(CellType::Empty, area[[2, 2]]);
+
+        let mut grid = Grid::new((20, 10), Topology::Toroidal);
+        grid.set(19, 9, CellType::Consumable(0));
+
+        let area = grid.get_area(Coord::<isize>::new(0, 0), 1);
+        assert_eq!(CellType::Consumable(0), area[[0, 0]]);
+    }
+
+    #[test]
+    fn test_step() {
+        let origin = Coord::<isize>::new(0, 5);
+
+        let grid = Grid::new((20, 10), Topology::Walled);
+        assert_eq!(None, grid.step(origin, Dir::W));
+        assert_eq!(
+            Some((Coord::<isize>::new(1, 6), Dir::NE)),
+            grid.step(origin, Dir::NE)
+        );
+
+        let grid = Grid::new((20, 10), Topology::Toroidal);
+        assert_eq!(
+            Some((Coord::<isize>::new(19, 6), Dir::NW)),
+            grid.step(origin, Dir::NW)
+        );
+
+        let grid = Grid::new((20, 10), Topology::Reflective);
+        assert_eq!(
+            Some((Coord::<isize>::new(1, 6), Dir::NE)),
+            grid.step(origin, Dir::NW)
+        );
+
+        let corner = Coord::<isize>::new(19, 9);
+        assert_eq!(
+            Some((Coord::<isize>::new(18, 8), Dir::SW)),
+            grid.step(corner, Dir::NE)
+        );
+    }
+
+    #[test]
+    fn test_occupant() {
+        let mut grid = Grid::new((10, 10), Topology::Walled);
+        let entity = Entity::from_raw_u32(7).unwrap();
+
+        grid.set(3, 4, CellType::Occupied(entity, 2));
+
+        assert_eq!(CellType::Occupied(entity, 2), grid.get(3, 4));
+        assert_eq!(CellType::Empty, grid.get(4, 3));
+        assert!(!grid.get(3, 4).is_passable());
     }
 }
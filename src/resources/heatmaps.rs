@@ -0,0 +1,231 @@
+use super::Parameters;
+use crate::components::Coord;
+use bevy::prelude::Resource;
+use std::collections::HashMap;
+
+/**Weight of the current tick at which everything accumulated is scaled back down*/
+const MAX_WEIGHT: f32 = 1e6;
+
+/**Quantity shown by the heatmap overlay*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeatmapLayer {
+    Density,
+    Energy,
+    Territory,
+    Consumption,
+    Deaths,
+    Traffic,
+}
+
+impl HeatmapLayer {
+    pub const ALL: [HeatmapLayer; 6] = [
+        HeatmapLayer::Density,
+        HeatmapLayer::Energy,
+        HeatmapLayer::Territory,
+        HeatmapLayer::Consumption,
+        HeatmapLayer::Deaths,
+        HeatmapLayer::Traffic,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HeatmapLayer::Density => "population density",
+            HeatmapLayer::Energy => "organism energy",
+            HeatmapLayer::Territory => "species territory",
+            HeatmapLayer::Consumption => "pellet consumption",
+            HeatmapLayer::Deaths => "deaths",
+            HeatmapLayer::Traffic => "movement traffic",
+        }
+    }
+
+    /**Layer shown after layer, cycling through every layer and then none*/
+    pub fn next(layer: Option<Self>) -> Option<Self> {
+        match layer {
+            None => Some(Self::ALL[0]),
+            Some(layer) => {
+                let i = Self::ALL
+                    .iter()
+                    .position(|l| *l == layer)
+                    .unwrap_or_default();
+                Self::ALL.get(i + 1).copied()
+            }
+        }
+    }
+}
+
+/**Statistics of square regions of the grid, ticks are weighted to decay over a window of ticks*/
+#[derive(Resource, Clone)]
+pub struct Heatmaps {
+    /**Cells per region side*/
+    resolution: usize,
+    shape: (usize, usize),
+    /**Weight of the current tick, grows every tick instead of decaying everything recorded so far*/
+    weight: f32,
+    /**Sum of the weights of all finished ticks*/
+    total: f32,
+    density: Vec<f32>,
+    energy: Vec<f32>,
+    territory: Vec<HashMap<usize, f32>>,
+    consumption: Vec<f32>,
+    deaths: Vec<f32>,
+    traffic: Vec<f32>,
+}
+
+impl Heatmaps {
+    pub fn new(params: &Parameters) -> Self {
+        let resolution = params.heatmap_resolution.max(1);
+        let shape = (
+            params.grid_width.div_ceil(resolution),
+            params.grid_height.div_ceil(resolution),
+        );
+        let n_regions = shape.0 * shape.1;
+
+        Self {
+            resolution,
+            shape,
+            weight: 1.,
+            total: 0.,
+            density: vec![0.; n_regions],
+            energy: vec![0.; n_regions],
+            territory: vec![HashMap::new(); n_regions],
+            consumption: vec![0.; n_regions],
+            deaths: vec![0.; n_regions],
+            traffic: vec![0.; n_regions],
+        }
+    }
+
+    #[inline]
+    pub fn resolution(&self) -> usize {
+        self.resolution
+    }
+
+    /**Number of regions along each axis*/
+    #[inline]
+    pub fn shape(&self) -> (usize, usize) {
+        self.shape
+    }
+
+    #[inline]
+    fn region(&self, coord: Coord<isize>) -> usize {
+        let x = (coord.x.max(0) as usize / self.resolution).min(self.shape.0 - 1);
+        let y = (coord.y.max(0) as usize / self.resolution).min(self.shape.1 - 1);
+        x + y * self.shape.0
+    }
+
+    /**Count an organism alive at coord during the current tick*/
+    pub fn sample(&mut self, coord: Coord<isize>, energy: f32, species: usize) {
+        let i = self.region(coord);
+        self.density[i] += self.weight;
+        self.energy[i] += self.weight * energy;
+        *self.territory[i].entry(species).or_default() += self.weight;
+    }
+
+    /**Count an event of an event layer at coord, other layers are sampled*/
+    pub fn record(&mut self, layer: HeatmapLayer, coord: Coord<isize>) {
+        let i = self.region(coord);
+        match layer {
+            HeatmapLayer::Consumption => self.consumption[i] += self.weight,
+            HeatmapLayer::Deaths => self.deaths[i] += self.weight,
+            HeatmapLayer::Traffic => self.traffic[i] += self.weight,
+            _ => {}
+        }
+    }
+
+    /**Finish the current tick, the weight of ticks older than window falls off exponentially*/
+    pub fn advance(&mut self, window: usize) {
+        self.total += self.weight;
+        self.weight /= 1. - 1. / (window.max(2) as f32);
+
+        if self.weight > MAX_WEIGHT {
+            let scale = 1. / self.weight;
+            for layer in [
+                &mut self.density,
+                &mut self.energy,
+                &mut self.consumption,
+                &mut self.deaths,
+                &mut self.traffic,
+            ] {
+                layer.iter_mut().for_each(|v| *v *= scale);
+            }
+            for counts in self.territory.iter_mut() {
+                counts.values_mut().for_each(|v| *v *= scale);
+                counts.retain(|_, v| *v > 1e-6);
+            }
+            self.total *= scale;
+            self.weight = 1.;
+        }
+    }
+
+    /**Average per tick of the region at index i, mean energy of the organisms found in it
+    and the share of the dominant species for territory*/
+    pub fn value(&self, layer: HeatmapLayer, i: usize) -> f32 {
+        if self.total == 0. {
+            return 0.;
+        }
+
+        match layer {
+            HeatmapLayer::Density => self.density[i] / self.total,
+            HeatmapLayer::Energy if self.density[i] > 0. => self.energy[i] / self.density[i],
+            HeatmapLayer::Energy => 0.,
+            HeatmapLayer::Territory => self.dominant(i).map_or(0., |(_, share)| share),
+            HeatmapLayer::Consumption => self.consumption[i] / self.total,
+            HeatmapLayer::Deaths => self.deaths[i] / self.total,
+            HeatmapLayer::Traffic => self.traffic[i] / self.total,
+        }
+    }
+
+    /**Species found most often in the region at index i with its share of the organisms found there*/
+    pub fn dominant(&self, i: usize) -> Option<(usize, f32)> {
+        let counts = &self.territory[i];
+        let (species, count) = counts
+            .iter()
+            .max_by(|a, b| a.1.total_cmp(b.1).then(b.0.cmp(a.0)))?;
+        Some((*species, count / counts.values().sum::<f32>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accumulation() {
+        let params = Parameters {
+            grid_width: 10,
+            grid_height: 7,
+            heatmap_resolution: 4,
+            ..Parameters::default()
+        };
+        let mut heatmaps = Heatmaps::new(&params);
+        assert_eq!((3, 2), heatmaps.shape());
+        assert_eq!(5, heatmaps.region(Coord::new(9, 6)));
+
+        //with a window of 2 every tick weighs twice as much as the previous one
+        heatmaps.sample(Coord::new(0, 0), 4., 1);
+        heatmaps.record(HeatmapLayer::Deaths, Coord::new(3, 3));
+        heatmaps.advance(2);
+        heatmaps.sample(Coord::new(1, 1), 1., 2);
+        heatmaps.sample(Coord::new(5, 1), 1., 2);
+        heatmaps.advance(2);
+
+        assert!((heatmaps.value(HeatmapLayer::Density, 0) - 1.).abs() < 1e-6);
+        assert!((heatmaps.value(HeatmapLayer::Energy, 0) - 2.).abs() < 1e-6);
+        assert!((heatmaps.value(HeatmapLayer::Deaths, 0) - 1. / 3.).abs() < 1e-6);
+        assert_eq!(0., heatmaps.value(HeatmapLayer::Traffic, 0));
+        let (species, share) = heatmaps.dominant(0).unwrap();
+        assert_eq!(2, species);
+        assert!((share - 2. / 3.).abs() < 1e-6);
+        assert_eq!(None, heatmaps.dominant(4));
+
+        //rescaling keeps the averages
+        for _ in 0..30 {
+            heatmaps.advance(2);
+        }
+        assert!(heatmaps.weight <= MAX_WEIGHT);
+        assert!(heatmaps.value(HeatmapLayer::Density, 0) < 1e-6);
+        assert!((heatmaps.value(HeatmapLayer::Energy, 0) - 2.).abs() < 1e-3);
+
+        assert_eq!(Some(HeatmapLayer::Density), HeatmapLayer::next(None));
+        assert_eq!(None, HeatmapLayer::next(Some(HeatmapLayer::Traffic)));
+    }
+}
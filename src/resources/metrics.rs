@@ -0,0 +1,234 @@
+use bevy::prelude::{Resource, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/**Output format of the metrics log*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetricsFormat {
+    /**One comma separated row per epoch after a header*/
+    #[default]
+    Csv,
+    /**One JSON object per epoch and line*/
+    JsonLines,
+}
+
+/**State of the population at the end of an epoch and what happened during it*/
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MetricsRow {
+    pub epoch: usize,
+    pub tick: usize,
+    pub population: usize,
+    pub n_species: usize,
+    pub org_energy: f32,
+    pub pellet_energy: f32,
+    pub births: usize,
+    pub deaths: BTreeMap<&'static str, usize>,
+    pub mean_genome_len: f32,
+    pub max_genome_len: usize,
+    pub mean_hidden_neurons: f32,
+    pub mean_edges: f32,
+    /**Number of times each action was chosen*/
+    pub actions: BTreeMap<String, usize>,
+}
+
+/**Births, deaths and actions counted over the current epoch, written out with the population state when it ends*/
+#[derive(Resource, Default)]
+pub struct Metrics {
+    pub epoch: usize,
+    births: usize,
+    deaths: BTreeMap<&'static str, usize>,
+    actions: Vec<usize>,
+    action_names: Vec<String>,
+    format: MetricsFormat,
+    writer: Option<BufWriter<File>>,
+    /**Row of the last finished epoch*/
+    pub last: Option<MetricsRow>,
+}
+
+impl Metrics {
+    /**Causes of death given a column even when nothing died of them*/
+    pub const CAUSES: [&str; 4] = ["starvation", "old_age", "predation", "stillbirth"];
+
+    /**Metrics streaming rows to the file at path, None keeps no log*/
+    pub fn new(path: Option<&str>, format: MetricsFormat, action_names: Vec<String>) -> Self {
+        let mut res = Self {
+            actions: vec![0; action_names.len()],
+            action_names,
+            format,
+            ..Self::default()
+        };
+
+        res.writer = path.and_then(|path| match res.open(path) {
+            Ok(writer) => Some(writer),
+            Err(e) => {
+                warn!("Failed to open metrics log {}: {}", path, e);
+                None
+            }
+        });
+        res
+    }
+
+    fn open(&self, path: &str) -> std::io::Result<BufWriter<File>> {
+        let path = Path::new(path);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        if self.format == MetricsFormat::Csv {
+            writeln!(writer, "{}", self.header())?;
+        }
+        Ok(writer)
+    }
+
+    #[inline]
+    pub fn birth(&mut self) {
+        self.births += 1;
+    }
+
+    #[inline]
+    pub fn death(&mut self, cause: &'static str) {
+        *self.deaths.entry(cause).or_default() += 1;
+    }
+
+    #[inline]
+    pub fn action(&mut self, action: usize) {
+        self.actions[action] += 1;
+    }
+
+    /**Complete the row of the finished epoch with the counters, write it and start counting for epoch*/
+    pub fn close_epoch(&mut self, epoch: usize, mut row: MetricsRow) -> &MetricsRow {
+        row.epoch = self.epoch;
+        row.births = self.births;
+        row.deaths = Self::CAUSES.iter().map(|c| (*c, 0)).collect();
+        row.deaths.extend(std::mem::take(&mut self.deaths));
+        row.actions = self
+            .action_names
+            .iter()
+            .cloned()
+            .zip(self.actions.iter().copied())
+            .collect();
+
+        self.epoch = epoch;
+        self.births = 0;
+        self.actions.fill(0);
+
+        if let Some(writer) = self.writer.as_mut() {
+            let line = match self.format {
+                MetricsFormat::Csv => Self::format_csv(&row),
+                MetricsFormat::JsonLines => serde_json::to_string(&row).unwrap_or_default(),
+            };
+            if let Err(e) = writeln!(writer, "{}", line).and_then(|_| writer.flush()) {
+                warn!("Failed to write metrics: {}", e);
+                self.writer = None;
+            }
+        }
+
+        self.last.insert(row)
+    }
+
+    /**Drop the counts of the current epoch and count again from epoch 0*/
+    pub fn reset(&mut self) {
+        self.epoch = 0;
+        self.births = 0;
+        self.deaths.clear();
+        self.actions.fill(0);
+    }
+
+    fn header(&self) -> String {
+        let mut names: Vec<String> = vec![
+            "epoch",
+            "tick",
+            "population",
+            "n_species",
+            "org_energy",
+            "pellet_energy",
+            "births",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        names.extend(Self::CAUSES.iter().map(|c| format!("deaths_{}", c)));
+        names.extend(
+            [
+                "mean_genome_len",
+                "max_genome_len",
+                "mean_hidden_neurons",
+                "mean_edges",
+            ]
+            .map(String::from),
+        );
+
+        let mut actions = self.action_names.clone();
+        actions.sort();
+        names.extend(actions.iter().map(|a| format!("action_{}", a)));
+        names.join(",")
+    }
+
+    fn format_csv(row: &MetricsRow) -> String {
+        let mut fields = vec![
+            row.epoch.to_string(),
+            row.tick.to_string(),
+            row.population.to_string(),
+            row.n_species.to_string(),
+            format!("{:.4}", row.org_energy),
+            format!("{:.4}", row.pellet_energy),
+            row.births.to_string(),
+        ];
+        fields.extend(Self::CAUSES.iter().map(|c| row.deaths[c].to_string()));
+        fields.extend([
+            format!("{:.4}", row.mean_genome_len),
+            row.max_genome_len.to_string(),
+            format!("{:.4}", row.mean_hidden_neurons),
+            format!("{:.4}", row.mean_edges),
+        ]);
+        fields.extend(row.actions.values().map(|n| n.to_string()));
+        fields.join(",")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rows() {
+        let mut metrics = Metrics::new(None, MetricsFormat::Csv, vec!["b".into(), "a".into()]);
+        metrics.birth();
+        metrics.death("predation");
+        metrics.action(0);
+        metrics.action(0);
+        metrics.action(1);
+
+        let row = metrics
+            .close_epoch(
+                1,
+                MetricsRow {
+                    tick: 200,
+                    population: 10,
+                    n_species: 2,
+                    org_energy: 3.,
+                    ..MetricsRow::default()
+                },
+            )
+            .clone();
+        assert_eq!(
+            "epoch,tick,population,n_species,org_energy,pellet_energy,births,\
+            deaths_starvation,deaths_old_age,deaths_predation,deaths_stillbirth,\
+            mean_genome_len,max_genome_len,mean_hidden_neurons,mean_edges,action_a,action_b",
+            metrics.header()
+        );
+        assert_eq!(
+            "0,200,10,2,3.0000,0.0000,1,0,0,1,0,0.0000,0,0.0000,0.0000,1,2",
+            Metrics::format_csv(&row)
+        );
+
+        //counters start over with the next epoch
+        let row = metrics.close_epoch(2, MetricsRow::default());
+        assert_eq!((1, 0, 0), (row.epoch, row.births, row.deaths["predation"]));
+        assert_eq!(Some(&0), row.actions.get("b"));
+    }
+}
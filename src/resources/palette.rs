@@ -0,0 +1,136 @@
+use bevy::color::{ColorToPacked, Oklcha, Srgba};
+use std::collections::BTreeMap;
+
+/**Perceptually distinct colours in OKLCH. A colour depends only on its key and on its parent's
+colour: founders spread over hues by the golden angle, children keep close to their parent's hue
+at another lightness. Colours written to a snapshot therefore determine those of every later species*/
+#[derive(Clone, Default)]
+pub struct Palette {
+    colors: BTreeMap<usize, (u8, u8, u8)>,
+}
+
+impl Palette {
+    pub const LIGHTNESS: [f32; 3] = [0.75, 0.62, 0.88];
+    pub const CHROMA: [f32; 2] = [0.15, 0.09];
+    const GOLDEN_ANGLE: f32 = 137.507_77;
+    /**Hue step in degrees between the children of a species*/
+    const CHILD_HUE_STEP: f32 = 20.;
+
+    /**Colour of key, near the hue of its parent's colour if it has one*/
+    pub fn color(key: usize, parent: Option<(u8, u8, u8)>) -> (u8, u8, u8) {
+        let (lightness, chroma, hue) = match parent {
+            Some(c) => {
+                let parent = Oklcha::from(Srgba::rgb_u8(c.0, c.1, c.2));
+                //one of the two lightness levels furthest from the parent's
+                let mut levels = Self::LIGHTNESS;
+                levels.sort_by(|a, b| {
+                    (a - parent.lightness)
+                        .abs()
+                        .total_cmp(&(b - parent.lightness).abs())
+                });
+                //siblings alternate sides of the parent's hue, moving further away
+                let step = key / 2;
+                let sign = if step.is_multiple_of(2) { 1. } else { -1. };
+                let offset = (1 + (step / 2) % 2) as f32 * Self::CHILD_HUE_STEP;
+                (
+                    levels[1 + key % 2],
+                    Self::CHROMA[0],
+                    parent.hue + sign * offset,
+                )
+            }
+            None => (
+                Self::LIGHTNESS[key % Self::LIGHTNESS.len()],
+                Self::CHROMA[(key / Self::LIGHTNESS.len()) % Self::CHROMA.len()],
+                key as f32 * Self::GOLDEN_ANGLE,
+            ),
+        };
+
+        let rgb = Srgba::from(Oklcha::new(lightness, chroma, hue.rem_euclid(360.), 1.))
+            .to_u8_array_no_alpha();
+        (rgb[0], rgb[1], rgb[2])
+    }
+
+    /**Record the colour of key, derived from parent's colour if it has one*/
+    pub fn allocate(&mut self, key: usize, parent: Option<usize>) -> (u8, u8, u8) {
+        let color = Self::color(key, parent.and_then(|p| self.get(p)));
+        self.colors.insert(key, color);
+        color
+    }
+
+    #[inline]
+    pub fn release(&mut self, key: usize) {
+        self.colors.remove(&key);
+    }
+
+    #[inline]
+    pub fn get(&self, key: usize) -> Option<(u8, u8, u8)> {
+        self.colors.get(&key).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::color::Oklaba;
+    use bevy::color::palettes::css::MIDNIGHT_BLUE;
+
+    /**Smallest OKLab distance between a child and its parent, between consecutive siblings,
+    between the first founders and to the background*/
+    const MIN_DISTANCE: f32 = 0.08;
+
+    #[inline]
+    fn distance(a: &Oklaba, b: &Oklaba) -> f32 {
+        ((a.lightness - b.lightness).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)).sqrt()
+    }
+
+    fn lab(c: (u8, u8, u8)) -> Oklaba {
+        Oklaba::from(Srgba::rgb_u8(c.0, c.1, c.2))
+    }
+
+    #[test]
+    fn test_separation() {
+        let mut palette = Palette::default();
+        let colors: Vec<_> = (0..20).map(|i| palette.allocate(i, None)).collect();
+
+        for (i, a) in colors.iter().enumerate() {
+            assert!(distance(&lab(*a), &Oklaba::from(MIDNIGHT_BLUE)) >= MIN_DISTANCE);
+            for b in colors[i + 1..].iter() {
+                assert!(distance(&lab(*a), &lab(*b)) >= MIN_DISTANCE);
+            }
+        }
+
+        //colours depend only on the key, not on the history
+        let mut other = Palette::default();
+        assert_eq!(colors[3], other.allocate(3, None));
+    }
+
+    #[test]
+    fn test_child_hue() {
+        let hue = |c: (u8, u8, u8)| Oklcha::from(Srgba::rgb_u8(c.0, c.1, c.2)).hue;
+        let mut palette = Palette::default();
+        for p in 0..6 {
+            palette.allocate(p, None);
+        }
+
+        for p in 0..6 {
+            let parent = palette.get(p).unwrap();
+            let children: Vec<_> = (6..30).map(|i| Palette::color(i, Some(parent))).collect();
+            for (i, child) in children.iter().enumerate() {
+                let diff = (hue(parent) - hue(*child)).rem_euclid(360.);
+                assert!(diff.min(360. - diff) < 60.);
+                assert!(distance(&lab(parent), &lab(*child)) >= MIN_DISTANCE);
+                assert!(distance(&Oklaba::from(MIDNIGHT_BLUE), &lab(*child)) >= MIN_DISTANCE);
+                if let Some(next) = children.get(i + 1) {
+                    assert!(distance(&lab(*child), &lab(*next)) >= MIN_DISTANCE);
+                }
+            }
+        }
+
+        //a parent's colour alone gives the colours of its children, e.g. after loading a snapshot
+        let child = palette.allocate(6, Some(0));
+        assert_eq!(Palette::color(6, palette.get(0)), child);
+
+        palette.release(0);
+        assert_eq!(None, palette.get(0));
+    }
+}
@@ -1,15 +1,334 @@
+use super::{Cycle, FoodKind, MetricsFormat, Topology};
+use crate::components::{Body, InternalSensor, Morphology, SensorySystem, Vision};
 use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
 
-#[derive(Resource, Clone, Copy)]
+pub const CONFIG_PATH: &str = "assets/config.ron";
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Parameters {
-    pub grid_size: usize,
+    pub grid_width: usize,
+    pub grid_height: usize,
+    pub topology: Topology,
     pub n_initial_entities: usize,
     pub n_initial_connections: usize,
     pub n_initial_neurons: usize,
     pub mutate_gene_proba: f64,
     pub insert_gene_proba: f64,
     pub delete_gene_proba: f64,
+    /**Base age in epochs after which organisms may die, scaled per organism by its lifespan gene*/
     pub lifespan: usize,
+    /**Largest side of the square of cells a body can occupy*/
+    pub max_body_size: usize,
+    /**Most cells a body can move per move action*/
+    pub max_speed: usize,
+    /**Energy spent per tick by every occupied cell for every cell of speed*/
+    pub body_cost: f32,
+    /**Energy spent per tick by an organism living for the base lifespan, proportional to its lifespan*/
+    pub longevity_cost: f32,
+    /**Initial largest genetic distance to a species representative for membership*/
+    pub speciation_threshold: f32,
+    /**Genetic distance under which a seen organism counts as kin, None counts its species*/
+    pub kin_distance: Option<f32>,
+    /**Number of species the threshold is adjusted towards every epoch, None keeps it fixed*/
+    pub target_species: Option<usize>,
+    /**Change of the threshold per epoch*/
+    pub threshold_step: f32,
+    /**Fraction of the energy missing from the world's capacity supplied as food every epoch*/
+    pub supply_rate: f32,
+    pub food: Vec<FoodKind>,
+    /**Decode per food kind digestive efficiencies from the genome*/
+    pub digestion: bool,
+    /**Energy drained from an attacked organism*/
+    pub attack_damage: f32,
+    /**Fraction of drained energy gained by the attacker*/
+    pub predation_efficiency: f32,
+    /**Food kind left behind by killed organisms*/
+    pub corpse_food: Option<usize>,
+    /**Energy given to the faced organism when sharing*/
+    pub share_energy: f32,
+    /**Distance over which signals are received*/
+    pub signal_range: usize,
+    /**Fraction of the difference to the neighbourhood mean scent diffused every tick*/
+    pub scent_diffusion: f32,
+    /**Fraction of scent evaporating every tick*/
+    pub scent_decay: f32,
+    /**Scent left by organisms per deposit*/
+    pub scent_deposit: f32,
+    /**Smell emitted by every food item per tick*/
+    pub food_smell: f32,
+    /**Energy spent per tick by every neuron and connection left after pruning*/
+    pub neuron_cost: f32,
+    pub edge_cost: f32,
+    /**Energy spent per tick by every gene*/
+    pub gene_cost: f32,
+    /**Energy spent per use of each action, actions missing from the map are free*/
+    pub action_costs: BTreeMap<String, f32>,
+    /**CSV file receiving the energy flows of every epoch*/
+    pub ledger_log: Option<String>,
+    /**CSV file receiving a record of every organism's life when it ends*/
+    pub genealogy_log: Option<String>,
+    /**Path without extension the phylogenetic tree is written to every epoch as Newick (.nwk) and JSON (.json)*/
+    pub phylogeny_log: Option<String>,
+    /**File a row of population metrics is written to at the end of every epoch*/
+    pub metrics_log: Option<String>,
+    pub metrics_format: MetricsFormat,
+    /**Directory snapshots of the population are saved to*/
+    pub snapshot_dir: String,
+    /**Localhost port of the control and telemetry API, None to disable*/
+    pub server_port: Option<u16>,
+    /**Cells per side of the regions heatmaps are accumulated over*/
+    pub heatmap_resolution: usize,
+    /**Ticks over which heatmap statistics decay*/
+    pub heatmap_window: usize,
+    /**Perception model*/
+    pub vision: Vision,
+    /**Farthest any organism can evolve to see, in cells*/
+    pub max_sensor_range: usize,
+    /**Energy spent per tick by the largest possible visual field*/
+    pub vision_cost: f32,
+    /**Senses of the organism's own state*/
+    pub internal_sensors: Vec<InternalSensor>,
+    /**Environmental cycles*/
+    pub schedule: Vec<Cycle>,
+    /**Seconds per tick at startup, epochs last 200 ticks*/
+    pub tick_seconds: f32,
+    #[serde(skip)]
     pub cell_width: f32,
+    #[serde(skip)]
     pub cell_height: f32,
 }
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self {
+            grid_width: 500,
+            grid_height: 500,
+            topology: Topology::Walled,
+            n_initial_entities: 100,
+            n_initial_connections: 25,
+            n_initial_neurons: 10,
+            mutate_gene_proba: 0.1,
+            insert_gene_proba: 0.08,
+            delete_gene_proba: 0.05,
+            lifespan: 15,
+            max_body_size: 2,
+            max_speed: 2,
+            body_cost: 5e-5,
+            longevity_cost: 5e-5,
+            speciation_threshold: 0.1,
+            kin_distance: None,
+            target_species: Some(20),
+            threshold_step: 0.005,
+            supply_rate: 0.05,
+            food: vec![
+                FoodKind::default(),
+                FoodKind {
+                    name: "meat".to_string(),
+                    energy: 0.3,
+                    supply: 0.,
+                    color: (178, 34, 34),
+                    ..Default::default()
+                },
+            ],
+            digestion: false,
+            attack_damage: 0.1,
+            predation_efficiency: 0.5,
+            corpse_food: Some(1),
+            share_energy: 0.05,
+            signal_range: 5,
+            scent_diffusion: 0.2,
+            scent_decay: 0.02,
+            scent_deposit: 1.,
+            food_smell: 0.05,
+            neuron_cost: 3e-8,
+            edge_cost: 2e-8,
+            gene_cost: 1e-8,
+            action_costs: BTreeMap::from_iter(
+                [
+                    ("move", 1e-4),
+                    ("attack", 1e-4),
+                    ("share", 1e-4),
+                    ("signal", 1e-4),
+                    ("deposit", 1e-4),
+                ]
+                .map(|(action, cost)| (action.to_string(), cost)),
+            ),
+            ledger_log: Some("logs/energy_ledger.csv".to_string()),
+            genealogy_log: Some("logs/genealogy.csv".to_string()),
+            phylogeny_log: Some("logs/phylogeny".to_string()),
+            metrics_log: Some("logs/metrics.csv".to_string()),
+            metrics_format: MetricsFormat::Csv,
+            snapshot_dir: "logs/snapshots".to_string(),
+            server_port: None,
+            heatmap_resolution: 5,
+            heatmap_window: 1000,
+            vision: Vision::Columns,
+            max_sensor_range: 10,
+            vision_cost: 1e-4,
+            internal_sensors: vec![
+                InternalSensor::Energy,
+                InternalSensor::Age,
+                InternalSensor::Heading,
+                InternalSensor::LastAction,
+                InternalSensor::Blocked,
+                InternalSensor::Oscillators(2),
+            ],
+            schedule: Vec::<Cycle>::new(),
+            tick_seconds: 0.05,
+            cell_width: 1.,
+            cell_height: 1.,
+        }
+    }
+}
+
+impl Parameters {
+    /**Load parameters from a RON file, missing fields take default values*/
+    pub fn load(path: &str) -> Result<Self, String> {
+        let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let res: Self = ron::from_str(&data).map_err(|e| e.to_string())?;
+        res.validate()?;
+        Ok(res)
+    }
+
+    /**Reject values the simulation can't run with*/
+    pub fn validate(&self) -> Result<(), String> {
+        for kind in self.food.iter() {
+            if !(kind.energy > 0. && kind.energy.is_finite()) {
+                return Err(format!("Food {} must have a positive energy", kind.name));
+            }
+            if !(kind.supply >= 0. && kind.supply.is_finite()) {
+                return Err(format!(
+                    "Food {} must have a non-negative supply",
+                    kind.name
+                ));
+            }
+        }
+        if let Some(kind) = self.corpse_food
+            && kind >= self.food.len()
+        {
+            return Err(format!("Corpse food kind {} isn't a food kind", kind));
+        }
+        if self.max_sensor_range == 0 {
+            return Err("Organisms must be able to see at least one cell away".to_string());
+        }
+        if !(self.tick_seconds > 0. && self.tick_seconds.is_finite()) {
+            return Err("Ticks must last a positive number of seconds".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let data = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        fs::write(path, data).map_err(|e| e.to_string())
+    }
+
+    /**Copy with the fields at dotted paths such as `food.0.energy` set to new values*/
+    pub fn with_overrides<'a>(
+        &self,
+        overrides: impl Iterator<Item = (&'a String, &'a serde_json::Value)>,
+    ) -> Result<Self, String> {
+        let mut res = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        for (path, value) in overrides {
+            let mut field = &mut res;
+            for key in path.split('.') {
+                field = match field {
+                    serde_json::Value::Array(a) => {
+                        key.parse::<usize>().ok().and_then(|i| a.get_mut(i))
+                    }
+                    serde_json::Value::Object(o) => o.get_mut(key),
+                    _ => None,
+                }
+                .ok_or(format!("No parameter {}", path))?;
+            }
+            *field = value.clone();
+        }
+
+        let mut res: Self = serde_json::from_value(res).map_err(|e| e.to_string())?;
+        (res.cell_width, res.cell_height) = (self.cell_width, self.cell_height);
+        res.validate()?;
+        Ok(res)
+    }
+
+    #[inline]
+    pub fn action_cost(&self, action: &str) -> f32 {
+        self.action_costs.get(action).copied().unwrap_or(0.)
+    }
+
+    /**Number of neuron genes decoded as phenotype traits rather than hidden neurons*/
+    pub fn n_trait_genes(&self) -> usize {
+        SensorySystem::N_SENSORS
+            + Morphology::N_GENES
+            + Body::N_GENES
+            + if self.digestion { self.food.len() } else { 0 }
+            + self.n_oscillator_genes()
+    }
+
+    #[inline]
+    pub fn n_oscillator_genes(&self) -> usize {
+        self.internal_sensors.iter().map(|s| s.n_genes()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::FoodDistribution;
+
+    #[test]
+    fn test_overrides() {
+        let overrides = BTreeMap::from([
+            ("mutate_gene_proba".to_string(), serde_json::json!(0.5)),
+            ("food.0.energy".to_string(), serde_json::json!(2)),
+            ("topology".to_string(), serde_json::json!("Toroidal")),
+        ]);
+        let params = Parameters::default()
+            .with_overrides(overrides.iter())
+            .unwrap();
+        assert_eq!(0.5, params.mutate_gene_proba);
+        assert_eq!(2., params.food[0].energy);
+        assert_eq!(Topology::Toroidal, params.topology);
+
+        let typo = BTreeMap::from([("food.9.energy".to_string(), serde_json::json!(1))]);
+        assert!(Parameters::default().with_overrides(typo.iter()).is_err());
+
+        //food without energy would fill the world with pellets, corpses must be food
+        for (path, value) in [
+            ("food.0.energy", 0),
+            ("food.0.supply", -1),
+            ("corpse_food", 5),
+            ("tick_seconds", 0),
+        ] {
+            let bad = BTreeMap::from([(path.to_string(), serde_json::json!(value))]);
+            assert!(Parameters::default().with_overrides(bad.iter()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_load_config() {
+        let params = Parameters::load(CONFIG_PATH).unwrap();
+        assert_eq!(Topology::Walled, params.topology);
+        assert_eq!(0., params.food[params.corpse_food.unwrap()].supply);
+
+        let params: Parameters = ron::from_str(
+            "(grid_width: 200, food: [(distribution: Patches(count: 5, radius: 20.0)), (name: \"meat\", supply: 0.0)])",
+        )
+        .unwrap();
+        assert_eq!(200, params.grid_width);
+        assert_eq!(Parameters::default().grid_height, params.grid_height);
+        assert_eq!(
+            FoodDistribution::Patches {
+                count: 5,
+                radius: 20.
+            },
+            params.food[0].distribution
+        );
+        assert_eq!(FoodKind::default().energy, params.food[0].energy);
+        assert_eq!("meat", params.food[1].name);
+        assert_eq!(FoodDistribution::Uniform, params.food[1].distribution);
+    }
+}
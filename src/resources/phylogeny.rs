@@ -0,0 +1,251 @@
+use super::Species;
+use crate::components::Genome;
+use bevy::prelude::Resource;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/**History of a species*/
+#[derive(Clone, Serialize)]
+pub struct Lineage {
+    pub species: usize,
+    /**Species it branched off from, None for founders*/
+    pub parent: Option<usize>,
+    /**Epoch of origin*/
+    pub birth: usize,
+    /**Epoch of extinction, None while alive*/
+    pub extinction: Option<usize>,
+    pub peak_population: usize,
+    /**Genome of the first member*/
+    pub genome: Genome,
+}
+
+/**Lineage of every species that has ever lived*/
+#[derive(Resource, Clone, Default)]
+pub struct Phylogeny {
+    lineages: BTreeMap<usize, Lineage>,
+    children: BTreeMap<usize, Vec<usize>>,
+    /**Last epoch the tree was updated in*/
+    pub epoch: usize,
+}
+
+impl Phylogeny {
+    /**Tree of the founding species, each represented by the genome of its first member*/
+    pub fn founders<'a>(members: impl Iterator<Item = (usize, &'a Genome)>) -> Self {
+        let mut res = Self::default();
+        for (species, genome) in members {
+            res.add(species, None, 0, genome);
+        }
+        res
+    }
+
+    /**Record the origin of a species, species already recorded are kept*/
+    pub fn add(&mut self, species: usize, parent: Option<usize>, epoch: usize, genome: &Genome) {
+        if self.lineages.contains_key(&species) {
+            return;
+        }
+
+        self.lineages.insert(
+            species,
+            Lineage {
+                species,
+                parent,
+                birth: epoch,
+                extinction: None,
+                peak_population: 0,
+                genome: genome.clone(),
+            },
+        );
+        if let Some(parent) = parent {
+            self.children.entry(parent).or_default().push(species);
+        }
+    }
+
+    /**Track peak populations and mark species no longer alive as extinct*/
+    pub fn update(&mut self, species: &Species, epoch: usize) {
+        self.epoch = epoch;
+        for lineage in self.lineages.values_mut() {
+            if lineage.extinction.is_some() {
+                continue;
+            }
+
+            match species.population(lineage.species) {
+                Some(n) => lineage.peak_population = lineage.peak_population.max(n),
+                None => lineage.extinction = Some(epoch),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, species: usize) -> Option<&Lineage> {
+        self.lineages.get(&species)
+    }
+
+    #[inline]
+    pub fn n_species(&self) -> usize {
+        self.lineages.len()
+    }
+
+    pub fn n_extinct(&self) -> usize {
+        self.lineages
+            .values()
+            .filter(|l| l.extinction.is_some())
+            .count()
+    }
+
+    /**The species followed by its ancestors up to the founder*/
+    pub fn ancestry(&self, species: usize) -> Vec<usize> {
+        let mut res = vec![species];
+        while let Some(parent) = self.get(*res.last().unwrap()).and_then(|l| l.parent) {
+            res.push(parent);
+        }
+        res
+    }
+
+    fn roots(&self) -> impl Iterator<Item = &Lineage> {
+        self.lineages.values().filter(|l| l.parent.is_none())
+    }
+
+    /**Tree in Newick format, species are labelled by id and branch lengths are the epochs
+    between the origins of a species and its parent*/
+    pub fn to_newick(&self) -> String {
+        enum Token {
+            Node(usize),
+            Close(usize),
+            Comma,
+        }
+
+        let label = |species: usize| {
+            let lineage = &self.lineages[&species];
+            let origin = lineage
+                .parent
+                .map(|parent| self.lineages[&parent].birth)
+                .unwrap_or(0);
+            format!("s{}:{}", species, lineage.birth.saturating_sub(origin))
+        };
+
+        //iterative traversal as lineages can be deeper than the stack allows
+        let mut res = String::from("(");
+        let mut stack = Vec::<Token>::new();
+        for (i, root) in self.roots().collect::<Vec<_>>().iter().rev().enumerate() {
+            if i > 0 {
+                stack.push(Token::Comma);
+            }
+            stack.push(Token::Node(root.species));
+        }
+
+        while let Some(token) = stack.pop() {
+            match token {
+                Token::Comma => res.push(','),
+                Token::Close(species) => {
+                    res.push(')');
+                    res += &label(species);
+                }
+                Token::Node(species) => match self.children.get(&species) {
+                    Some(children) => {
+                        res.push('(');
+                        stack.push(Token::Close(species));
+                        for (i, child) in children.iter().rev().enumerate() {
+                            if i > 0 {
+                                stack.push(Token::Comma);
+                            }
+                            stack.push(Token::Node(*child));
+                        }
+                    }
+                    None => res += &label(species),
+                },
+            }
+        }
+
+        res + ");"
+    }
+
+    /**Every lineage with its parent in JSON format*/
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.lineages.values().collect::<Vec<_>>()).unwrap_or_default()
+    }
+
+    /**Indented tree of living species and their ancestors, at most max_lines long*/
+    pub fn outline(&self, max_lines: usize) -> String {
+        //lineages with living descendants
+        let mut surviving = BTreeSet::<usize>::new();
+        for lineage in self.lineages.values().filter(|l| l.extinction.is_none()) {
+            for species in self.ancestry(lineage.species) {
+                if !surviving.insert(species) {
+                    break;
+                }
+            }
+        }
+
+        let mut lines = Vec::<String>::new();
+        let mut stack: Vec<(usize, usize)> = self
+            .roots()
+            .filter(|l| surviving.contains(&l.species))
+            .map(|l| (l.species, 0))
+            .collect();
+        stack.reverse();
+
+        while let Some((species, depth)) = stack.pop() {
+            if lines.len() == max_lines {
+                lines.push("...".to_string());
+                break;
+            }
+
+            let lineage = &self.lineages[&species];
+            lines.push(format!(
+                "{}{} {} born {} peak {}",
+                "  ".repeat(depth.min(10)),
+                if lineage.extinction.is_some() {
+                    "+"
+                } else {
+                    "-"
+                },
+                species,
+                lineage.birth,
+                lineage.peak_population
+            ));
+
+            for child in self.children.get(&species).into_iter().flatten().rev() {
+                if surviving.contains(child) {
+                    stack.push((*child, depth + 1));
+                }
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_phylogeny() {
+        let genome = Genome::new(3, &mut rand::rng());
+        let mut species = Species::new(HashSet::from_iter(0..2));
+        species.increment_species(0);
+        species.increment_species(1);
+
+        let mut phylogeny = Phylogeny::default();
+        phylogeny.add(0, None, 0, &genome);
+        phylogeny.add(1, None, 0, &genome);
+        phylogeny.add(2, Some(0), 3, &genome);
+        phylogeny.add(3, Some(2), 5, &genome);
+        phylogeny.add(4, Some(0), 6, &genome);
+        assert_eq!(vec![3, 2, 0], phylogeny.ancestry(3));
+        assert_eq!("(((s3:2)s2:3,s4:6)s0:0,s1:0);", phylogeny.to_newick());
+
+        //species 2, 3 and 4 aren't alive in Species
+        species.increment_species(0);
+        phylogeny.update(&species, 7);
+        assert_eq!(2, phylogeny.get(0).unwrap().peak_population);
+        assert_eq!(Some(7), phylogeny.get(3).unwrap().extinction);
+        assert_eq!(3, phylogeny.n_extinct());
+
+        species.decrement_species(1);
+        phylogeny.update(&species, 8);
+        assert_eq!(Some(8), phylogeny.get(1).unwrap().extinction);
+        assert_eq!("- 0 born 0 peak 2", phylogeny.outline(10));
+    }
+}
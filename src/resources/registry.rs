@@ -0,0 +1,288 @@
+use super::{EnergyLedger, Environment, Grid, Parameters, ScentField, Signals};
+use crate::components::*;
+use bevy::prelude::{App, Entity, Resource};
+use rand::rngs::StdRng;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/**World and organism state visible to sensors*/
+pub struct SenseContext<'a> {
+    pub entity: Entity,
+    pub org: &'a Organism,
+    pub body: &'a Body,
+    pub ss: &'a SensorySystem,
+    pub proprioception: &'a Proprioception,
+    pub coord: Coord<isize>,
+    pub dir: Dir,
+    pub grid: &'a Grid,
+    pub environment: &'a Environment,
+    pub signals: &'a Signals,
+    pub scent: &'a ScentField,
+    pub params: &'a Parameters,
+    pub tick: usize,
+    /**Genomes of living organisms, only filled when kin is told by genetic distance*/
+    pub genomes: &'a HashMap<Entity, Genome>,
+}
+
+impl SenseContext<'_> {
+    /**Cell at the centre of the organism's body, wrapped onto the world*/
+    pub fn centre(&self) -> Coord<isize> {
+        self.grid
+            .resolve(self.body.centre(self.coord))
+            .unwrap_or(self.coord)
+    }
+}
+
+/**World and organism state effectors may change*/
+pub struct ActContext<'a> {
+    pub entity: Entity,
+    pub org: &'a mut Organism,
+    pub body: &'a Body,
+    pub digestion: &'a Digestion,
+    pub coord: &'a mut Coord<isize>,
+    pub dir: &'a mut Dir,
+    pub proprioception: &'a mut Proprioception,
+    pub metabolism: &'a mut Metabolism,
+    pub grid: &'a mut Grid,
+    pub signals: &'a mut Signals,
+    pub scent: &'a mut ScentField,
+    pub params: &'a Parameters,
+    pub ledger: &'a mut EnergyLedger,
+    /**Coordinates of eaten pellets*/
+    pub eaten: &'a mut Vec<Coord<isize>>,
+    /**Organism to interact with once every organism has acted*/
+    pub target: Option<Entity>,
+    pub rng: &'a mut StdRng,
+}
+
+impl ActContext<'_> {
+    /**Take energy from the actor, recorded under the cause*/
+    pub fn spend(&mut self, cause: &'static str, amount: f32) {
+        self.org.sub_energy(amount);
+        self.metabolism.record(cause, amount);
+        self.ledger.record(cause, -amount);
+    }
+
+    /**Pay the configured cost of an action*/
+    pub fn pay(&mut self, action: &'static str) {
+        self.spend(action, self.params.action_cost(action));
+    }
+
+    /**Cell at the centre of the actor's body, wrapped onto the world*/
+    pub fn centre(&self) -> Coord<isize> {
+        self.grid
+            .resolve(self.body.centre(*self.coord))
+            .unwrap_or(*self.coord)
+    }
+
+    /**Organism in the first cell beyond the actor's body in the direction it is facing*/
+    pub fn faced_organism(&self) -> Option<Entity> {
+        let mut coord = *self.coord;
+        for _ in 0..self.body.size {
+            (coord, _) = self.grid.step(coord, *self.dir)?;
+            match self.grid.get(coord.x as usize, coord.y as usize) {
+                CellType::Occupied(entity, _) if entity == self.entity => continue,
+                CellType::Occupied(entity, _) => return Some(entity),
+                _ => return None,
+            }
+        }
+        None
+    }
+}
+
+/**Source of network inputs*/
+pub trait Sensor: Send + Sync + 'static {
+    /**Number of inputs written per organism, n_actions is the number of registered actions*/
+    fn n_inputs(&self, params: &Parameters, n_actions: usize) -> usize;
+
+    fn sense(&self, ctx: &SenseContext, inputs: &mut Vec<f32>);
+}
+
+/**Sink of network outputs*/
+pub trait Effector: Send + Sync + 'static {
+    /**Name used to look up action costs and to label spent energy*/
+    fn name(&self) -> &'static str;
+
+    /**Number of mutually exclusive actions, each with its own output neuron*/
+    fn n_actions(&self) -> usize;
+
+    /**Label of every action, the name suffixed with the action index when there are several*/
+    fn action_names(&self) -> Vec<String> {
+        match self.n_actions() {
+            1 => vec![self.name().to_string()],
+            n => (0..n).map(|i| format!("{}_{}", self.name(), i)).collect(),
+        }
+    }
+
+    /**Number of continuous outputs passed along when one of the actions is chosen*/
+    fn n_values(&self) -> usize {
+        0
+    }
+
+    fn act(&self, action: usize, values: &[f32], ctx: &mut ActContext);
+
+    /**Resolve an interaction with the target set in ActContext, killed targets leave a corpse*/
+    fn interact(
+        &self,
+        _action: usize,
+        _actor: &mut Organism,
+        _target: &mut Organism,
+        _params: &Parameters,
+    ) {
+    }
+}
+
+/**Sensors and effectors making up the network inputs and outputs in registration order*/
+#[derive(Resource, Clone, Default)]
+pub struct Registry {
+    sensors: Vec<Arc<dyn Sensor>>,
+    effectors: Vec<Arc<dyn Effector>>,
+}
+
+impl Registry {
+    pub fn add_sensor(&mut self, sensor: impl Sensor) {
+        self.sensors.push(Arc::new(sensor));
+    }
+
+    pub fn add_effector(&mut self, effector: impl Effector) {
+        self.effectors.push(Arc::new(effector));
+    }
+
+    pub fn n_actions(&self) -> usize {
+        self.effectors.iter().map(|e| e.n_actions()).sum()
+    }
+
+    /**Labels of the actions in output order*/
+    pub fn action_names(&self) -> Vec<String> {
+        self.effectors
+            .iter()
+            .flat_map(|e| e.action_names())
+            .collect()
+    }
+
+    pub fn n_inputs(&self, params: &Parameters) -> usize {
+        let n_actions = self.n_actions();
+        self.sensors
+            .iter()
+            .map(|s| s.n_inputs(params, n_actions))
+            .sum()
+    }
+
+    /**Action outputs of every effector followed by their value outputs*/
+    pub fn ns_shape(&self, params: &Parameters, hidden: usize) -> NsShape {
+        let n_values = self.effectors.iter().map(|e| e.n_values()).sum::<usize>();
+        NsShape::new(self.n_inputs(params), hidden, self.n_actions() + n_values)
+    }
+
+    pub fn sense(&self, ctx: &SenseContext) -> Vec<f32> {
+        let mut res = Vec::<f32>::new();
+        for sensor in self.sensors.iter() {
+            sensor.sense(ctx, &mut res);
+        }
+        res
+    }
+
+    /**Effector owning the action, the action index within it and its slice of the value outputs*/
+    pub fn resolve<'a>(
+        &self,
+        action: usize,
+        values: &'a [f32],
+    ) -> (Arc<dyn Effector>, usize, &'a [f32]) {
+        let (mut action_offset, mut value_offset) = (0, 0);
+        for effector in self.effectors.iter() {
+            if action < action_offset + effector.n_actions() {
+                let values = &values[value_offset..value_offset + effector.n_values()];
+                return (effector.clone(), action - action_offset, values);
+            }
+            action_offset += effector.n_actions();
+            value_offset += effector.n_values();
+        }
+        panic!("Action doesn't exist: {}", action);
+    }
+}
+
+/**Registration of sensors and effectors on the app*/
+pub trait RegistryAppExt {
+    fn add_sensor(&mut self, sensor: impl Sensor) -> &mut Self;
+
+    fn add_effector(&mut self, effector: impl Effector) -> &mut Self;
+}
+
+impl RegistryAppExt for App {
+    fn add_sensor(&mut self, sensor: impl Sensor) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<Registry>()
+            .add_sensor(sensor);
+        self
+    }
+
+    fn add_effector(&mut self, effector: impl Effector) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_init::<Registry>()
+            .add_effector(effector);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Probe(usize, usize);
+
+    impl Effector for Probe {
+        fn name(&self) -> &'static str {
+            "probe"
+        }
+
+        fn n_actions(&self) -> usize {
+            self.0
+        }
+
+        fn n_values(&self) -> usize {
+            self.1
+        }
+
+        fn act(&self, _action: usize, _values: &[f32], _ctx: &mut ActContext) {}
+    }
+
+    impl Sensor for Probe {
+        fn n_inputs(&self, _params: &Parameters, n_actions: usize) -> usize {
+            self.0 + n_actions
+        }
+
+        fn sense(&self, _ctx: &SenseContext, _inputs: &mut Vec<f32>) {}
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = Registry::default();
+        registry.add_effector(Probe(2, 0));
+        registry.add_effector(Probe(1, 2));
+        registry.add_effector(Probe(3, 1));
+        registry.add_sensor(Probe(4, 0));
+
+        let shape = registry.ns_shape(&Parameters::default(), 5);
+        assert_eq!(10, shape.input);
+        assert_eq!(9, shape.output);
+
+        let values = [0.1, 0.2, 0.3];
+        let (effector, action, res) = registry.resolve(1, &values);
+        assert_eq!((2, 1, 0), (effector.n_actions(), action, res.len()));
+
+        let (effector, action, res) = registry.resolve(2, &values);
+        assert_eq!((1, 0), (effector.n_actions(), action));
+        assert_eq!([0.1, 0.2], res);
+
+        let (_, action, res) = registry.resolve(5, &values);
+        assert_eq!(2, action);
+        assert_eq!([0.3], res);
+
+        assert_eq!(
+            vec![
+                "probe_0", "probe_1", "probe", "probe_0", "probe_1", "probe_2"
+            ],
+            registry.action_names()
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use super::{Grid, Parameters, Topology};
+use crate::components::{Coord, Dir};
+use bevy::prelude::Resource;
+use ndarray::Array3;
+
+/**Diffusing and evaporating scent, channel 0 is deposited by organisms, the rest are smells of food kinds*/
+#[derive(Resource, Clone)]
+pub struct ScentField {
+    data: Array3<f32>,
+    wrap: bool,
+}
+
+impl ScentField {
+    /**Sensor values per channel: local intensity, forward and lateral gradient*/
+    pub const N_CHANNEL_INPUTS: usize = 3;
+
+    pub fn new(n_food_kinds: usize, shape: (usize, usize), topology: Topology) -> Self {
+        Self {
+            data: Array3::<f32>::zeros((n_food_kinds + 1, shape.0, shape.1)),
+            wrap: topology == Topology::Toroidal,
+        }
+    }
+
+    #[inline]
+    pub fn n_inputs(n_food_kinds: usize) -> usize {
+        Self::N_CHANNEL_INPUTS * (n_food_kinds + 1)
+    }
+
+    #[inline]
+    pub fn n_channels(&self) -> usize {
+        self.data.dim().0
+    }
+
+    #[inline]
+    pub fn get(&self, channel: usize, x: usize, y: usize) -> f32 {
+        self.data[[channel, x, y]]
+    }
+
+    #[inline]
+    pub fn deposit(&mut self, coord: Coord<isize>, amount: f32) {
+        self.data[[0, coord.x as usize, coord.y as usize]] += amount;
+    }
+
+    /**Emit food smells, then diffuse towards the neighbourhood mean and evaporate*/
+    pub fn update(&mut self, grid: &Grid, params: &Parameters) {
+        for (coord, kind) in grid.get_food_coords() {
+            self.data[[kind + 1, coord.x as usize, coord.y as usize]] += params.food_smell;
+        }
+
+        let (n_channels, width, height) = self.data.dim();
+        let prev = self.data.clone();
+        let keep = 1. - params.scent_decay;
+
+        for c in 0..n_channels {
+            for x in 0..width {
+                let (west, east) = self.neighbors(x, width);
+                for y in 0..height {
+                    let (south, north) = self.neighbors(y, height);
+                    let value = prev[[c, x, y]];
+                    let mean = (prev[[c, west, y]]
+                        + prev[[c, east, y]]
+                        + prev[[c, x, south]]
+                        + prev[[c, x, north]])
+                        * 0.25;
+
+                    self.data[[c, x, y]] = (value + params.scent_diffusion * (mean - value)) * keep;
+                }
+            }
+        }
+    }
+
+    /**Intensity and gradients relative to the heading for every channel, squashed to (-1, 1)*/
+    pub fn sense(&self, grid: &Grid, origin: Coord<isize>, dir: Dir) -> Vec<f32> {
+        let forward = dir.value();
+        let right = Coord::new(forward.y, -forward.x);
+        let mut res = Vec::<f32>::with_capacity(Self::N_CHANNEL_INPUTS * self.n_channels());
+
+        for c in 0..self.n_channels() {
+            let value = self.get(c, origin.x as usize, origin.y as usize);
+            let sample = |offset: Coord<isize>| match grid.resolve(origin + offset) {
+                Some(coord) => self.get(c, coord.x as usize, coord.y as usize),
+                None => value,
+            };
+
+            res.push(value.tanh());
+            res.push((sample(forward) - sample(Coord::new(-forward.x, -forward.y))).tanh());
+            res.push((sample(right) - sample(Coord::new(-right.x, -right.y))).tanh());
+        }
+
+        res
+    }
+
+    /**Lower and upper neighbour indices, edges reflect scent back unless the world wraps*/
+    #[inline]
+    fn neighbors(&self, i: usize, n: usize) -> (usize, usize) {
+        if self.wrap {
+            ((i + n - 1) % n, (i + 1) % n)
+        } else {
+            (i.saturating_sub(1), (i + 1).min(n - 1))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::CellType;
+
+    fn params() -> Parameters {
+        Parameters {
+            scent_diffusion: 0.5,
+            scent_decay: 0.,
+            food_smell: 1.,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diffusion() {
+        let grid = Grid::new((5, 5), Topology::Walled);
+        let mut field = ScentField::new(1, (5, 5), Topology::Walled);
+        field.deposit(Coord::new(2, 2), 1.);
+        field.update(&grid, &params());
+
+        assert_eq!(0.5, field.get(0, 2, 2));
+        assert_eq!(0.125, field.get(0, 2, 3));
+        assert_eq!(0., field.get(0, 3, 3));
+        assert!((1. - field.data.sum()).abs() < 1e-6);
+
+        let mut params = params();
+        params.scent_decay = 0.5;
+        field.update(&grid, &params);
+        assert!((0.5 - field.data.sum()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_food_smell_and_gradient() {
+        let mut grid = Grid::new((5, 5), Topology::Toroidal);
+        grid.set(4, 2, CellType::Consumable(0));
+        let mut field = ScentField::new(1, (5, 5), Topology::Toroidal);
+        field.update(&grid, &params());
+
+        //smell wraps around the world edge
+        assert_eq!(0.125, field.get(1, 0, 2));
+
+        let res = field.sense(&grid, Coord::new(3, 2), Dir::E);
+        assert_eq!(ScentField::n_inputs(1), res.len());
+        assert_eq!(0., res[0]);
+        assert_eq!(0.125_f32.tanh(), res[3]);
+        assert_eq!(0.5_f32.tanh(), res[4]);
+        assert_eq!(0., res[5]);
+
+        let res = field.sense(&grid, Coord::new(3, 2), Dir::N);
+        assert_eq!(0., res[4]);
+        assert_eq!(0.5_f32.tanh(), res[5]);
+    }
+}
@@ -0,0 +1,82 @@
+use super::Grid;
+use crate::components::Coord;
+use bevy::prelude::Resource;
+use ndarray::Array2;
+
+/**Signals emitted by organisms, received by neighbours on the next tick*/
+#[derive(Resource, Clone)]
+pub struct Signals {
+    current: Array2<f32>,
+    next: Array2<f32>,
+}
+
+impl Signals {
+    pub const N_INPUTS: usize = 1;
+
+    pub fn new(shape: (usize, usize)) -> Self {
+        Self {
+            current: Array2::<f32>::zeros(shape),
+            next: Array2::<f32>::zeros(shape),
+        }
+    }
+
+    #[inline]
+    pub fn emit(&mut self, coord: Coord<isize>, value: f32) {
+        self.next[[coord.x as usize, coord.y as usize]] = value;
+    }
+
+    /**Make signals emitted during the last tick receivable*/
+    pub fn swap(&mut self) {
+        std::mem::swap(&mut self.current, &mut self.next);
+        self.next.fill(0.);
+    }
+
+    /**Sum of signals emitted by others within range, squashed to (-1, 1)*/
+    pub fn receive(
+        &self,
+        grid: &Grid,
+        origin: Coord<isize>,
+        range: usize,
+    ) -> [f32; Self::N_INPUTS] {
+        let range = range as isize;
+        let mut res = 0.;
+
+        for x in -range..=range {
+            for y in -range..=range {
+                if x == 0 && y == 0 {
+                    continue;
+                }
+                if let Some(coord) = grid.resolve(origin + Coord::new(x, y)) {
+                    res += self.current[[coord.x as usize, coord.y as usize]];
+                }
+            }
+        }
+
+        [res.tanh()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::Topology;
+
+    #[test]
+    fn test_signals() {
+        let grid = Grid::new((10, 10), Topology::Walled);
+        let mut signals = Signals::new((10, 10));
+        let origin = Coord::<isize>::new(5, 5);
+
+        signals.emit(Coord::new(6, 6), 0.5);
+        signals.emit(Coord::new(5, 5), 1.);
+        signals.emit(Coord::new(9, 9), 1.);
+        assert_eq!([0.], signals.receive(&grid, origin, 2));
+
+        //own signal and those out of range are not received
+        signals.swap();
+        assert_eq!([0.5_f32.tanh()], signals.receive(&grid, origin, 2));
+
+        signals.swap();
+        assert_eq!([0.], signals.receive(&grid, origin, 2));
+    }
+}
@@ -0,0 +1,19 @@
+use bevy::prelude::Resource;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+/**Source of every random draw of the simulation, runs with the same seed and parameters replay the same history*/
+#[derive(Resource)]
+pub struct SimRng(pub StdRng);
+
+impl SimRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Default for SimRng {
+    fn default() -> Self {
+        Self(StdRng::from_os_rng())
+    }
+}
@@ -5,4 +5,5 @@ pub struct SimState {
     pub paused: bool,
     pub reset: bool,
     pub epoch: usize,
+    pub tick: usize,
 }
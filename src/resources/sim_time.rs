@@ -1,6 +1,18 @@
+use super::EpochTime;
 use bevy::prelude::{Resource, Timer};
+use std::time::Duration;
 
 #[derive(Resource)]
 pub struct SimTime {
     pub timer: Timer,
 }
+
+impl SimTime {
+    /**Change the duration of a tick, epochs keep their length in ticks*/
+    pub fn set_tick_duration(&mut self, epoch_time: &mut EpochTime, seconds: f32) {
+        let scale = seconds / self.timer.duration().as_secs_f32();
+        let epoch = epoch_time.timer.duration().mul_f32(scale);
+        self.timer.set_duration(Duration::from_secs_f32(seconds));
+        epoch_time.timer.set_duration(epoch);
+    }
+}
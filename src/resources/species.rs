@@ -1,3 +1,4 @@
+use super::Palette;
 use crate::components::Genome;
 use bevy::prelude::Color;
 use bevy::prelude::Resource;
@@ -9,37 +10,34 @@ pub struct Species {
     species: HashSet<usize>,
     population_count: HashMap<usize, usize>,
     species_count: usize,
-    colors: HashSet<(u8, u8, u8)>,
-    color_map: HashMap<usize, (u8, u8, u8)>,
+    palette: Palette,
+    /**Genome new members are compared with*/
+    representatives: HashMap<usize, Genome>,
+    /**Largest genetic distance to a representative for membership*/
+    pub threshold: f32,
 }
 
 impl Species {
     pub fn new(species: HashSet<usize>) -> Self {
-        let mut rng = rand::rng();
-        let mut colors = HashSet::<(u8, u8, u8)>::with_capacity(species.len());
         let species_count = species.len();
 
         let population_count = HashMap::<usize, usize>::from_iter(species.iter().map(|s| (*s, 0)));
 
-        while colors.len() < species_count {
-            colors.insert((
-                rng.random_range(0..u8::MAX),
-                rng.random_range(0..u8::MAX),
-                rng.random_range(0..u8::MAX),
-            ));
-        }
-
-        let mut color_map = HashMap::<usize, (u8, u8, u8)>::with_capacity(species_count);
-        for (s, c) in species.iter().zip(colors.iter()) {
-            color_map.insert(*s, *c);
+        //allocate in id order so that colours don't depend on hashing
+        let mut palette = Palette::default();
+        let mut ids: Vec<usize> = species.iter().copied().collect();
+        ids.sort();
+        for s in ids.into_iter() {
+            palette.allocate(s, None);
         }
 
         Self {
             species,
             population_count,
             species_count,
-            colors,
-            color_map,
+            palette,
+            representatives: HashMap::new(),
+            threshold: 1e-1,
         }
     }
 
@@ -47,6 +45,7 @@ impl Species {
         let mut assigned_species = vec![0; genomes.len()];
         let mut unassigned = HashSet::<usize>::from_iter(0..genomes.len());
         let mut species = HashSet::<usize>::new();
+        let mut representatives = HashMap::<usize, Genome>::new();
         let mut species_count = 0;
 
         for (i, genome) in genomes.iter().enumerate() {
@@ -55,6 +54,7 @@ impl Species {
             }
 
             assigned_species[i] = species_count;
+            representatives.insert(species_count, (*genome).clone());
 
             let species_members: Vec<_> = unassigned
                 .iter()
@@ -75,6 +75,8 @@ impl Species {
         }
 
         let mut res = Self::new(species);
+        res.representatives = representatives;
+        res.threshold = genetic_threshold;
         for s in assigned_species.iter() {
             res.increment_species(*s);
         }
@@ -87,6 +89,12 @@ impl Species {
         self.species.len()
     }
 
+    /**Number of members of a living species*/
+    #[inline]
+    pub fn population(&self, species: usize) -> Option<usize> {
+        self.population_count.get(&species).copied()
+    }
+
     #[inline]
     pub fn topk(&self, k: usize) -> Vec<(usize, usize)> {
         //reverse key and value so that elements would be ordered by value
@@ -102,26 +110,17 @@ impl Species {
             .collect()
     }
 
+    /**Allocate a new species represented by the genome, coloured after its parent species*/
     #[inline]
-    pub fn add_species(&mut self) -> usize {
+    pub fn add_species(&mut self, representative: Genome, parent: Option<usize>) -> usize {
         let species = self.species_count + 1;
 
         if self.species.insert(species) {
             self.species_count += 1;
             self.population_count.insert(species, 0);
+            self.representatives.insert(species, representative);
 
-            let mut rng = rand::rng();
-            loop {
-                let color = (
-                    rng.random_range(0..u8::MAX),
-                    rng.random_range(0..u8::MAX),
-                    rng.random_range(0..u8::MAX),
-                );
-                if self.colors.insert(color) {
-                    self.color_map.insert(species, color);
-                    break;
-                }
-            }
+            self.palette.allocate(species, parent);
         }
 
         species
@@ -131,10 +130,75 @@ impl Species {
     pub fn remove_species(&mut self, species: &usize) {
         self.species.remove(species);
         self.population_count.remove(species);
+        self.representatives.remove(species);
+
+        self.palette.release(*species);
+    }
+
+    /**Assign every genome to its current species if still compatible with its representative,
+    otherwise to the first compatible species or to a new one it represents. Representatives are
+    then redrawn from the members, empty species removed and the threshold moved towards
+    the target number of species. Returns the assigned species in genome order*/
+    pub fn speciate(
+        &mut self,
+        genomes: &[&Genome],
+        current: &[usize],
+        target: Option<usize>,
+        step: f32,
+        rng: &mut impl Rng,
+    ) -> Vec<usize> {
+        let mut order: Vec<usize> = self.species.iter().copied().collect();
+        order.sort();
+
+        let mut assigned = Vec::<usize>::with_capacity(genomes.len());
+        for (genome, species) in genomes.iter().zip(current.iter()) {
+            let compatible = |s: &usize| {
+                self.representatives
+                    .get(s)
+                    .is_some_and(|r| genome.get_distance(r) < self.threshold)
+            };
+
+            let found = if compatible(species) {
+                Some(*species)
+            } else {
+                order.iter().find(|s| compatible(s)).copied()
+            };
+
+            let species = found.unwrap_or_else(|| {
+                let s = self.add_species((*genome).clone(), Some(*species));
+                order.push(s);
+                s
+            });
+            assigned.push(species);
+        }
+
+        //recount members and draw new representatives among them
+        let mut members = HashMap::<usize, Vec<usize>>::new();
+        for (i, species) in assigned.iter().enumerate() {
+            members.entry(*species).or_default().push(i);
+        }
+
+        for species in order.iter() {
+            match members.get(species) {
+                Some(m) => {
+                    self.population_count.insert(*species, m.len());
+                    let representative = genomes[m[rng.random_range(0..m.len())]];
+                    self.representatives
+                        .insert(*species, representative.clone());
+                }
+                None => self.remove_species(species),
+            }
+        }
+
+        if let Some(target) = target {
+            if self.len() > target {
+                self.threshold += step;
+            } else if self.len() < target {
+                self.threshold = (self.threshold - step).max(step);
+            }
+        }
 
-        let color = *self.color_map.get(species).unwrap();
-        self.colors.remove(&color);
-        self.color_map.remove(species);
+        assigned
     }
 
     #[inline]
@@ -154,9 +218,15 @@ impl Species {
         }
     }
 
+    /**Colour of a living species as an sRGB triple*/
+    #[inline]
+    pub fn get_rgb(&self, species: usize) -> Option<(u8, u8, u8)> {
+        self.palette.get(species)
+    }
+
     #[inline]
     pub fn get_color(&self, species: usize) -> Color {
-        if let Some(color) = self.color_map.get(&species) {
+        if let Some(color) = self.palette.get(species) {
             return Color::srgb_u8(color.0, color.1, color.2);
         }
 
@@ -174,38 +244,38 @@ mod tests {
         let genomes = vec![
             //species 0: two equal genomes
             Genome::from(vec![
-                Gene(0b010_1001001_1111010_000011010000011),
-                Gene(0b011_1001001_1111010_000011010000011),
-                Gene(0b011_1001001_1111110_000011010000011),
+                Gene(0b010_1001001111_1010000_011010000011),
+                Gene(0b011_1001001111_1010000_011010000011),
+                Gene(0b011_1001001111_1110000_011010000011),
             ]),
             Genome::from(vec![
-                Gene(0b010_1001001_1111010_000011010000011),
-                Gene(0b011_1001001_1111010_000011010000011),
-                Gene(0b011_1001001_1111110_000011010000011),
+                Gene(0b010_1001001111_1010000_011010000011),
+                Gene(0b011_1001001111_1010000_011010000011),
+                Gene(0b011_1001001111_1110000_011010000011),
             ]),
             //species 0: slight weight difference
             Genome::from(vec![
-                Gene(0b010_1001001_1111010_000011010000111),
-                Gene(0b011_1001001_1111010_000011010000011),
-                Gene(0b011_1001001_1111110_000011010000011),
+                Gene(0b010_1001001111_1010000_011010000111),
+                Gene(0b011_1001001111_1010000_011010000011),
+                Gene(0b011_1001001111_1110000_011010000011),
             ]),
             //species 1: ~30% difference
             Genome::from(vec![
-                Gene(0b010_1001001_1111010_000011010000011),
-                Gene(0b011_1001001_1111010_000010010000011),
-                Gene(0b011_1001011_1111110_000011010000011),
+                Gene(0b010_1001001111_1010000_011010000011),
+                Gene(0b011_1001001111_1010000_010010000011),
+                Gene(0b011_1001011111_1110000_011010000011),
             ]),
             //species 1: slight weight difference
             Genome::from(vec![
-                Gene(0b010_1001001_1111010_000011010000011),
-                Gene(0b011_1001001_1111010_000010010010011),
-                Gene(0b011_1001011_1111110_000011010000011),
+                Gene(0b010_1001001111_1010000_011010000011),
+                Gene(0b011_1001001111_1010000_010010010011),
+                Gene(0b011_1001011111_1110000_011010000011),
             ]),
             //species 2
             Genome::from(vec![
-                Gene(0b011_1001001_1011010_010011010000011),
-                Gene(0b001_1001001_1011010_001010010010011),
-                Gene(0b010_1001011_1111010_010011010000011),
+                Gene(0b011_1001001101_1010010_011010000011),
+                Gene(0b001_1001001101_1010001_010010010011),
+                Gene(0b010_1001011111_1010010_011010000011),
             ]),
         ];
 
@@ -213,6 +283,23 @@ mod tests {
             Species::from_genomes(&(genomes.iter().map(|g| g).collect()), 1e-1);
         assert_eq!(3, species.len());
         assert_eq!(vec![0, 0, 0, 1, 1, 2], clustered);
+
+        //misassigned genome moves to the compatible species, species 2 dies out
+        let mut species = species;
+        let genomes: Vec<_> = genomes.iter().collect();
+        let rng = &mut rand::rng();
+        let assigned = species.speciate(&genomes[..5], &[0, 0, 0, 0, 1], Some(1), 0.05, rng);
+        assert_eq!(vec![0, 0, 0, 1, 1], assigned);
+        assert_eq!(2, species.len());
+        assert_eq!(Some(2), species.population(1));
+        assert_eq!(None, species.population(2));
+        assert!((species.threshold - 0.15).abs() < 1e-6);
+
+        //incompatible genome founds a new species
+        let assigned = species.speciate(&genomes[5..], &[0], None, 0.05, rng);
+        assert_eq!(1, species.len());
+        assert_eq!(Some(1), species.population(assigned[0]));
+        assert!(![0, 1, 2].contains(&assigned[0]));
     }
 
     #[test]
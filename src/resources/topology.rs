@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/**World edge behaviour*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Topology {
+    /**Edges block movement and are visible as walls*/
+    Walled,
+    /**Edges wrap around to the opposite side*/
+    Toroidal,
+    /**Edges block movement and bounce organisms back*/
+    Reflective,
+}
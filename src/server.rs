@@ -0,0 +1,375 @@
+use crate::components::{Coord, Metabolism, Organism};
+use crate::resources::*;
+use bevy::prelude::*;
+use http::{Request, Response};
+use serde_json::{Map, Value, json};
+use std::fs;
+use std::io;
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+mod http;
+
+/**Parameters that can't change without rebuilding the world and its view or that change the layout
+of genomes and inputs, pellets on the grid are worth the energy of their kind*/
+const FIXED_PARAMETERS: [&str; 14] = [
+    "grid_width",
+    "grid_height",
+    "topology",
+    "heatmap_resolution",
+    "food",
+    "digestion",
+    "vision",
+    "internal_sensors",
+    "corpse_food",
+    "max_body_size",
+    "max_sensor_range",
+    "n_initial_entities",
+    "n_initial_connections",
+    "n_initial_neurons",
+];
+/**Time a connection waits for the simulation to answer*/
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/**Request waiting to be handled on the simulation thread with the channel its response goes back on*/
+type Job = (Request, Sender<Response>);
+
+/**Control and telemetry server on localhost, requests are handled between simulation steps*/
+#[derive(Resource)]
+pub struct Server {
+    requests: Mutex<Receiver<Job>>,
+    /**Metrics rows for the broadcasting thread to stream to WebSocket subscribers*/
+    rows: Sender<String>,
+}
+
+impl Server {
+    pub fn start(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let (subscriber_sender, subscriber_receiver) = mpsc::channel();
+        let (rows, rows_receiver) = mpsc::channel();
+
+        thread::spawn(move || broadcast(rows_receiver, subscriber_receiver));
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                let subscribers = subscriber_sender.clone();
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream, sender, subscribers) {
+                        warn!("Failed to handle API request: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            requests: Mutex::new(receiver),
+            rows,
+        })
+    }
+}
+
+/**Send every row to every WebSocket subscriber, dropping those that disconnected,
+so that slow subscribers hold up this thread rather than the simulation*/
+fn broadcast(rows: Receiver<String>, new_subscribers: Receiver<WebSocket<TcpStream>>) {
+    let mut subscribers = Vec::new();
+    for row in rows.iter() {
+        subscribers.extend(new_subscribers.try_iter());
+        subscribers.retain_mut(|socket| socket.send(Message::text(row.clone())).is_ok());
+    }
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    sender: Sender<Job>,
+    subscribers: Sender<WebSocket<TcpStream>>,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let request = http::read_request(&stream)?;
+
+    if request.is_websocket() {
+        if request.path != "/metrics" {
+            return http::write_response(&mut stream, &Response::error(404, "No such stream"));
+        }
+        stream.set_write_timeout(Some(Duration::from_secs(1)))?;
+        subscribers
+            .send(http::accept_websocket(stream, &request)?)
+            .ok();
+        return Ok(());
+    }
+
+    let (reply, response) = mpsc::channel();
+    let response = match sender.send((request, reply)) {
+        Ok(()) => response
+            .recv_timeout(RESPONSE_TIMEOUT)
+            .unwrap_or_else(|_| Response::error(500, "Simulation not responding")),
+        Err(_) => Response::error(500, "Simulation stopped"),
+    };
+    http::write_response(&mut stream, &response)
+}
+
+pub fn server_startup_system(mut commands: Commands, params: Res<Parameters>) {
+    let Some(port) = params.server_port else {
+        return;
+    };
+
+    match Server::start(port) {
+        Ok(server) => {
+            info!("API listening on http://localhost:{}", port);
+            commands.insert_resource(server);
+        }
+        Err(e) => warn!("Failed to start API server on port {}: {}", port, e),
+    }
+}
+
+/**Answer pending API requests and stream the metrics of finished epochs*/
+pub fn server_system(
+    server: Res<Server>,
+    mut sim_state: ResMut<SimState>,
+    (mut sim_time, mut epoch_time): (ResMut<SimTime>, ResMut<EpochTime>),
+    mut params: ResMut<Parameters>,
+    (species, phylogeny, metrics): (Res<Species>, Res<Phylogeny>, Res<Metrics>),
+    orgs_query: Query<(&Organism, &Coord<isize>, &Metabolism)>,
+    mut streamed: Local<Option<(usize, usize)>>,
+) {
+    if let Some(row) = &metrics.last
+        && *streamed != Some((row.epoch, row.tick))
+    {
+        *streamed = Some((row.epoch, row.tick));
+        let row = serde_json::to_string(row).unwrap_or_default();
+        server.rows.send(row).ok();
+    }
+
+    let requests = server.requests.lock().unwrap();
+    while let Ok((request, reply)) = requests.try_recv() {
+        let path = request.path.split('?').next().unwrap_or_default();
+        let body = || serde_json::from_str::<Value>(&request.body).map_err(|e| e.to_string());
+
+        let response = match (request.method.as_str(), path) {
+            ("GET", "/state") => Response::ok(state_json(&sim_state, &sim_time, &epoch_time)),
+            ("POST", "/pause" | "/resume") => {
+                sim_state.paused = path == "/pause";
+                Response::ok(state_json(&sim_state, &sim_time, &epoch_time))
+            }
+            ("POST", "/reset") => {
+                sim_state.reset = true;
+                Response::ok(state_json(&sim_state, &sim_time, &epoch_time))
+            }
+            ("POST", "/speed") => match body().map(|b| b["tick_seconds"].as_f64()) {
+                Ok(Some(seconds)) if seconds > 0. && seconds.is_finite() => {
+                    sim_time.set_tick_duration(&mut epoch_time, seconds as f32);
+                    Response::ok(state_json(&sim_state, &sim_time, &epoch_time))
+                }
+                Ok(_) => Response::error(400, "Expected a positive tick_seconds"),
+                Err(e) => Response::error(400, &e),
+            },
+            ("GET", "/population") => {
+                let (population, energy) = orgs_query
+                    .iter()
+                    .fold((0, 0.), |(n, e), (org, ..)| (n + 1, e + org.energy));
+                Response::ok(
+                    json!({
+                        "population": population,
+                        "energy": energy,
+                        "n_species": species.len(),
+                        "n_lineages": phylogeny.n_species(),
+                        "n_extinct": phylogeny.n_extinct(),
+                        "species": species_json(&species),
+                    })
+                    .to_string(),
+                )
+            }
+            ("GET", "/organisms") => {
+                let orgs: Vec<Value> = orgs_query
+                    .iter()
+                    .map(|(org, coord, _)| {
+                        json!({
+                            "id": org.id,
+                            "species": org.species,
+                            "energy": org.energy,
+                            "coord": [coord.x, coord.y],
+                        })
+                    })
+                    .collect();
+                Response::ok(Value::from(orgs).to_string())
+            }
+            ("GET", _) if path.starts_with("/organisms/") => {
+                let id = path["/organisms/".len()..].parse::<usize>().ok();
+                match orgs_query.iter().find(|(org, ..)| Some(org.id) == id) {
+                    Some((org, coord, metabolism)) => {
+                        Response::ok(organism_json(org, coord, metabolism).to_string())
+                    }
+                    None => Response::error(404, "No living organism with that id"),
+                }
+            }
+            ("GET", "/parameters") => {
+                Response::ok(serde_json::to_string(&*params).unwrap_or_default())
+            }
+            ("POST", "/parameters") => match body() {
+                Ok(Value::Object(overrides)) => match override_parameters(&params, &overrides) {
+                    Ok(new_params) => {
+                        *params = new_params;
+                        Response::ok(serde_json::to_string(&*params).unwrap_or_default())
+                    }
+                    Err(e) => Response::error(400, &e),
+                },
+                Ok(_) => Response::error(400, "Expected an object of parameter paths and values"),
+                Err(e) => Response::error(400, &e),
+            },
+            ("POST", "/snapshot") => {
+                match save_snapshot(&sim_state, &params, &species, &orgs_query) {
+                    Ok(path) => Response::ok(json!({ "path": path }).to_string()),
+                    Err(e) => Response::error(500, &e),
+                }
+            }
+            ("GET", "/metrics") => Response::ok(
+                metrics
+                    .last
+                    .as_ref()
+                    .and_then(|row| serde_json::to_string(row).ok())
+                    .unwrap_or("null".to_string()),
+            ),
+            (
+                _,
+                "/state" | "/pause" | "/resume" | "/reset" | "/speed" | "/population"
+                | "/organisms" | "/parameters" | "/snapshot" | "/metrics",
+            ) => Response::error(405, "Method not allowed"),
+            _ => Response::error(404, "No such endpoint"),
+        };
+
+        reply.send(response).ok();
+    }
+}
+
+/**Parameters with the overrides applied and validated, fails on any fixed parameter*/
+fn override_parameters(
+    params: &Parameters,
+    overrides: &Map<String, Value>,
+) -> Result<Parameters, String> {
+    let fixed = |path: &str| {
+        let name = path.split('.').next().unwrap_or_default();
+        FIXED_PARAMETERS.contains(&name)
+    };
+    match overrides.keys().find(|path| fixed(path)) {
+        Some(path) => Err(format!("{} can't be changed at runtime", path)),
+        None => params.with_overrides(overrides.iter()),
+    }
+}
+
+fn state_json(sim_state: &SimState, sim_time: &SimTime, epoch_time: &EpochTime) -> String {
+    json!({
+        "paused": sim_state.paused,
+        "epoch": sim_state.epoch,
+        "tick": sim_state.tick,
+        "tick_seconds": sim_time.timer.duration().as_secs_f64(),
+        "epoch_seconds": epoch_time.timer.duration().as_secs_f64(),
+    })
+    .to_string()
+}
+
+fn species_json(species: &Species) -> Vec<Value> {
+    species
+        .topk(species.len())
+        .iter()
+        .map(|(s, population)| {
+            json!({
+                "species": s,
+                "population": population,
+                "color": species.get_rgb(*s).map(|c| [c.0, c.1, c.2]),
+            })
+        })
+        .collect()
+}
+
+fn organism_json(org: &Organism, coord: &Coord<isize>, metabolism: &Metabolism) -> Value {
+    json!({
+        "id": org.id,
+        "parent": org.parent,
+        "species": org.species,
+        "coord": [coord.x, coord.y],
+        "energy": org.energy,
+        "age": org.age,
+        "birth": org.birth,
+        "offspring": org.offspring,
+        "gathered": org.gathered,
+        "spent": metabolism.spent,
+        "genome": org.genome,
+    })
+}
+
+/**Write the parameters, the colour of every living species and every living organism with its
+genome to a JSON file, returns its path*/
+fn save_snapshot(
+    sim_state: &SimState,
+    params: &Parameters,
+    species: &Species,
+    orgs_query: &Query<(&Organism, &Coord<isize>, &Metabolism)>,
+) -> Result<String, String> {
+    let snapshot = json!({
+        "epoch": sim_state.epoch,
+        "tick": sim_state.tick,
+        "parameters": params,
+        "species": species_json(species),
+        "organisms": orgs_query
+            .iter()
+            .map(|(org, coord, metabolism)| organism_json(org, coord, metabolism))
+            .collect::<Vec<_>>(),
+    });
+
+    fs::create_dir_all(&params.snapshot_dir).map_err(|e| e.to_string())?;
+    let path = format!("{}/snapshot_{}.json", params.snapshot_dir, sim_state.tick);
+    fs::write(&path, snapshot.to_string()).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_organism_json() {
+        let org = Organism::new(0.5, 0, &mut rand::rng());
+        let mut metabolism = Metabolism::default();
+        metabolism.record("brain", 0.25);
+        metabolism.record("move", 0.5);
+
+        let value = organism_json(&org, &Coord::new(3, 4), &metabolism);
+        assert_eq!(json!([3, 4]), value["coord"]);
+        assert_eq!(json!({ "brain": 0.25, "move": 0.5 }), value["spent"]);
+    }
+
+    #[test]
+    fn test_species_json() {
+        let species = Species::new(std::collections::HashSet::from([0, 1]));
+        let value = species_json(&species);
+        let (r, g, b) = Palette::color(1, None);
+        assert_eq!(2, value.len());
+        assert!(value.contains(&json!({ "species": 1, "population": 0, "color": [r, g, b] })));
+    }
+
+    #[test]
+    fn test_override_parameters() {
+        let params = Parameters::default();
+        let overrides = |value: Value| value.as_object().unwrap().clone();
+
+        let new_params =
+            override_parameters(&params, &overrides(json!({ "supply_rate": 0.5 }))).unwrap();
+        assert_eq!(0.5, new_params.supply_rate);
+
+        //changes to the world, food kinds, genomes or inputs and invalid values are refused
+        for bad in [
+            json!({ "food": [] }),
+            json!({ "food.0.energy": 0.5 }),
+            json!({ "topology": "Toroidal" }),
+            json!({ "internal_sensors.0": "Energy" }),
+            json!({ "digestion": true, "mutate_gene_proba": 0.5 }),
+            json!({ "supply_rate": "high" }),
+        ] {
+            assert!(override_parameters(&params, &overrides(bad)).is_err());
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use tungstenite::WebSocket;
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+
+/**Largest accepted request body*/
+const MAX_BODY: usize = 1 << 20;
+
+/**Parsed HTTP/1.1 request, header names are lowercase*/
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+/**Status code and JSON body*/
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    pub fn ok(body: String) -> Self {
+        Self { status: 200, body }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+impl Request {
+    pub fn is_websocket(&self) -> bool {
+        self.headers
+            .get("upgrade")
+            .is_some_and(|v| v.eq_ignore_ascii_case("websocket"))
+    }
+}
+
+pub fn read_request(stream: &TcpStream) -> io::Result<Request> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let mut reader = BufReader::new(stream);
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(invalid("Malformed request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut headers = HashMap::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let Some((name, value)) = line.trim_end().split_once(':') else {
+            break;
+        };
+        headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+
+    let len = headers
+        .get("content-length")
+        .and_then(|l| l.parse::<usize>().ok())
+        .unwrap_or(0);
+    if len > MAX_BODY {
+        return Err(invalid("Request body too large"));
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body: String::from_utf8(body).map_err(|_| invalid("Body isn't UTF-8"))?,
+    })
+}
+
+pub fn write_response(stream: &mut TcpStream, response: &Response) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.body.len(),
+        response.body
+    )
+}
+
+/**Complete the WebSocket opening handshake, the stream then carries WebSocket frames*/
+pub fn accept_websocket(
+    mut stream: TcpStream,
+    request: &Request,
+) -> io::Result<WebSocket<TcpStream>> {
+    let key = request
+        .headers
+        .get("sec-websocket-key")
+        .ok_or(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Missing Sec-WebSocket-Key",
+        ))?;
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.as_bytes())
+    )?;
+    Ok(WebSocket::from_raw_socket(stream, Role::Server, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, TcpListener};
+    use std::thread;
+    use tungstenite::Message;
+
+    #[test]
+    fn test_handshake() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let url = format!("ws://{}/metrics", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let request = read_request(&stream).unwrap();
+            assert!(request.is_websocket());
+            let mut socket = accept_websocket(stream, &request).unwrap();
+            socket.send(Message::text("hi")).unwrap();
+        });
+
+        let (mut socket, _) = tungstenite::connect(url).unwrap();
+        assert_eq!(Message::text("hi"), socket.read().unwrap());
+        server.join().unwrap();
+    }
+}
@@ -1,9 +1,14 @@
 use crate::resources::*;
+use crate::server::*;
 use crate::systems::*;
 use bevy::prelude::*;
 
+/**Simulation, runs with or without a window*/
 pub struct SimPlugin;
 
+/**Rendering controls and info panels of the windowed app*/
+pub struct ViewPlugin;
+
 impl Plugin for SimPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(SimTime {
@@ -16,13 +21,65 @@ impl Plugin for SimPlugin {
             paused: false,
             reset: false,
             epoch: 0,
+            tick: 0,
         })
-        .add_systems(Startup, (sim_startup_system, ui_startup_system))
-        .add_systems(Update, (input_system, sim_step_system, epoch_system, reset_system))
+        .add_sensor(VisionSensor)
+        .add_sensor(EnvironmentSensor)
+        .add_sensor(SignalSensor)
+        .add_sensor(ScentSensor)
+        .add_sensor(ProprioceptionSensor)
+        .add_effector(IdleEffector)
+        .add_effector(MoveEffector)
+        .add_effector(AttackEffector)
+        .add_effector(ShareEffector)
+        .add_effector(SignalEffector)
+        .add_effector(DepositEffector)
+        .add_systems(Startup, (
+            sim_startup_system,
+            server_startup_system.after(sim_startup_system),
+        ))
+        .init_resource::<EnergyLedger>()
+        .init_resource::<SimRng>()
+        .add_systems(Update, (
+            parameters_system
+                .before(sim_step_system)
+                .run_if(resource_changed::<Parameters>),
+            sim_step_system,
+            epoch_system,
+            reset_system,
+            energy_ledger_system
+                .after(sim_step_system)
+                .after(epoch_system)
+                .after(reset_system),
+            speciation_system.after(epoch_system).after(reset_system),
+            metrics_system
+                .after(sim_step_system)
+                .after(speciation_system),
+            phylogeny_system
+                .after(sim_step_system)
+                .after(speciation_system),
+            server_system
+                .after(metrics_system)
+                .run_if(resource_exists::<Server>),
+        ));
+    }
+}
+
+impl Plugin for ViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, ui_startup_system)
+        .add_systems(Update, (
+            (input_system, camera_system, minimap_system).chain(),
+            parameter_panel_system,
+        ))
         .add_systems(PostUpdate, (
             energy_info_system,
+            ledger_info_system,
             epoch_info_system,
+            heatmap_overlay_system,
+            phylogeny_view_system,
             population_info_system,
+            scent_overlay_system,
             species_info_system,
         ));
     }
@@ -1,13 +1,19 @@
 use crate::components::*;
 
+mod camera_system;
+mod effectors;
 mod entities_spawn;
 mod input_system;
+mod sensors;
 mod simulation_system;
 mod startup_system;
 mod ui_system;
 
+pub use camera_system::*;
+pub use effectors::*;
 use entities_spawn::*;
 pub use input_system::*;
+pub use sensors::*;
 pub use simulation_system::*;
 pub use startup_system::*;
 pub use ui_system::*;
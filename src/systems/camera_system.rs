@@ -0,0 +1,95 @@
+use crate::components::ui::*;
+use crate::components::{Body, CellType, Organism};
+use crate::resources::*;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+/**Area covered by the grid in world coordinates, cells are centred on multiples of the cell size*/
+pub fn world_rect(params: &Parameters) -> Rect {
+    Rect::new(
+        -0.5 * params.cell_width,
+        -0.5 * params.cell_height,
+        (params.grid_width as f32 - 0.5) * params.cell_width,
+        (params.grid_height as f32 - 0.5) * params.cell_height,
+    )
+}
+
+/**Fit the world into view with F, follow the organism or species label clicked and keep the followed organism centred*/
+pub fn camera_system(
+    (keys, mouse): (Res<ButtonInput<KeyCode>>, Res<ButtonInput<MouseButton>>),
+    (params, grid): (Res<Parameters>, Res<Grid>),
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform, &mut Transform, &mut Follow)>,
+    interactions_query: Query<&Interaction>,
+    labels_query: Query<(&Interaction, &SpeciesText), Changed<Interaction>>,
+    orgs_query: Query<(Entity, &Organism, &Body, &Transform), Without<Camera>>,
+) {
+    let (camera, global_transform, mut transform, mut follow) = camera.into_inner();
+    let world = world_rect(&params);
+
+    //panning by hand stops following
+    if keys.just_pressed(KeyCode::Escape)
+        || keys.any_pressed([KeyCode::KeyW, KeyCode::KeyA, KeyCode::KeyS, KeyCode::KeyD])
+    {
+        follow.clear();
+    }
+
+    if keys.just_pressed(KeyCode::KeyF) {
+        follow.clear();
+        let scale = (world.width() / window.width()).max(world.height() / window.height());
+        transform.translation = world.center().extend(transform.translation.z);
+        transform.scale = Vec3::splat(scale);
+    }
+
+    for (interaction, label) in labels_query.iter() {
+        if *interaction == Interaction::Pressed {
+            follow.organism = None;
+            follow.species = Some(label.0);
+        }
+    }
+
+    //clicks over ui nodes are theirs, clicks on the world select the organism under the cursor
+    if mouse.just_pressed(MouseButton::Left)
+        && interactions_query.iter().all(|i| *i == Interaction::None)
+        && let Some(cursor) = window.cursor_position()
+        && let Ok(position) = camera.viewport_to_world_2d(global_transform, cursor)
+    {
+        follow.clear();
+        let x = (position.x / params.cell_width).round();
+        let y = (position.y / params.cell_height).round();
+        if (0. ..grid.width() as f32).contains(&x)
+            && (0. ..grid.height() as f32).contains(&y)
+            && let CellType::Occupied(e, _) = grid.get(x as usize, y as usize)
+        {
+            follow.organism = Some(e);
+        }
+    }
+
+    if follow.organism.is_some_and(|e| !orgs_query.contains(e)) {
+        follow.organism = None;
+    }
+    if follow.organism.is_none()
+        && let Some(species) = follow.species
+    {
+        follow.organism = orgs_query
+            .iter()
+            .filter(|(_, org, ..)| org.species == species)
+            .max_by(|a, b| {
+                a.2.size
+                    .cmp(&b.2.size)
+                    .then(a.1.energy.total_cmp(&b.1.energy))
+            })
+            .map(|(e, ..)| e);
+        //extinct species can't be followed
+        if follow.organism.is_none() {
+            follow.species = None;
+        }
+    }
+
+    if let Some(e) = follow.organism
+        && let Ok((.., target)) = orgs_query.get(e)
+    {
+        transform.translation.x = target.translation.x;
+        transform.translation.y = target.translation.y;
+    }
+}
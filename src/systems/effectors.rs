@@ -0,0 +1,272 @@
+use crate::components::*;
+use crate::resources::*;
+use rand::Rng;
+
+/**Doing nothing*/
+pub struct IdleEffector;
+
+impl Effector for IdleEffector {
+    fn name(&self) -> &'static str {
+        "idle"
+    }
+
+    fn n_actions(&self) -> usize {
+        1
+    }
+
+    fn act(&self, _action: usize, _values: &[f32], _ctx: &mut ActContext) {}
+}
+
+/**Moving forward, in a random direction or backward, and rotating clockwise or counterclockwise before moving, as many cells as the body's speed*/
+pub struct MoveEffector;
+
+impl Effector for MoveEffector {
+    fn name(&self) -> &'static str {
+        "move"
+    }
+
+    fn n_actions(&self) -> usize {
+        5
+    }
+
+    fn action_names(&self) -> Vec<String> {
+        ["forward", "random", "back", "rotate", "rotate_counter"]
+            .map(|a| format!("move_{}", a))
+            .to_vec()
+    }
+
+    fn act(&self, action: usize, _values: &[f32], ctx: &mut ActContext) {
+        let dir = match action {
+            0 => *ctx.dir,
+            1 => ctx.rng.random(),
+            2 => -*ctx.dir,
+            3 => ctx.dir.rotate(),
+            _ => ctx.dir.rotate_counter(),
+        };
+        *ctx.dir = dir;
+
+        for _ in 0..ctx.body.speed {
+            if !step(ctx) {
+                ctx.proprioception.blocked = true;
+                return;
+            }
+        }
+    }
+}
+
+/**Move the whole body one cell in its direction, eating the food it covers, false if blocked*/
+fn step(ctx: &mut ActContext) -> bool {
+    //world bounds check
+    let Some((next_coord, dir)) = ctx.grid.step(*ctx.coord, *ctx.dir) else {
+        return false;
+    };
+    let Some(next_cells) = ctx.grid.footprint(next_coord, ctx.body.size) else {
+        return false;
+    };
+    *ctx.dir = dir;
+
+    //collision check, cells of the body itself are free to move into
+    if next_cells
+        .iter()
+        .any(|c| match ctx.grid.get(c.x as usize, c.y as usize) {
+            CellType::Occupied(entity, _) => entity != ctx.entity,
+            cell => !cell.is_passable(),
+        })
+    {
+        return false;
+    }
+
+    for c in next_cells.iter() {
+        if let CellType::Consumable(kind) = ctx.grid.get(c.x as usize, c.y as usize) {
+            let energy = ctx.params.food[kind].energy;
+            let digested = energy * ctx.digestion.get(kind);
+            ctx.org.add_energy(digested); // consuming pellet gives energy
+            ctx.org.gathered += digested;
+            ctx.ledger.record("digestion", digested - energy);
+            let clamped = ctx.org.clamp_energy();
+            ctx.ledger.record("clamp", clamped);
+
+            ctx.eaten.push(*c);
+        }
+    }
+
+    //bigger bodies are more expensive to move
+    ctx.spend(
+        "move",
+        ctx.params.action_cost("move") * ctx.body.n_cells() as f32,
+    );
+
+    ctx.grid
+        .set_footprint(*ctx.coord, ctx.body.size, CellType::Empty);
+    ctx.grid.set_footprint(
+        next_coord,
+        ctx.body.size,
+        CellType::Occupied(ctx.entity, ctx.org.species),
+    );
+
+    *ctx.coord = next_coord;
+    true
+}
+
+/**Draining energy from the faced organism*/
+pub struct AttackEffector;
+
+impl Effector for AttackEffector {
+    fn name(&self) -> &'static str {
+        "attack"
+    }
+
+    fn n_actions(&self) -> usize {
+        1
+    }
+
+    fn act(&self, _action: usize, _values: &[f32], ctx: &mut ActContext) {
+        ctx.pay(self.name());
+        ctx.target = ctx.faced_organism();
+    }
+
+    fn interact(
+        &self,
+        _action: usize,
+        actor: &mut Organism,
+        target: &mut Organism,
+        params: &Parameters,
+    ) {
+        let drained = params.attack_damage.min(target.energy);
+        target.sub_energy(params.attack_damage);
+        let before = actor.energy;
+        actor.add_energy(drained * params.predation_efficiency);
+        actor.clamp_energy();
+        actor.gathered += actor.energy - before;
+    }
+}
+
+/**Giving energy to the faced organism*/
+pub struct ShareEffector;
+
+impl Effector for ShareEffector {
+    fn name(&self) -> &'static str {
+        "share"
+    }
+
+    fn n_actions(&self) -> usize {
+        1
+    }
+
+    fn act(&self, _action: usize, _values: &[f32], ctx: &mut ActContext) {
+        ctx.pay(self.name());
+        ctx.target = ctx.faced_organism();
+    }
+
+    fn interact(
+        &self,
+        _action: usize,
+        actor: &mut Organism,
+        target: &mut Organism,
+        params: &Parameters,
+    ) {
+        let given = params.share_energy.min(actor.energy.max(0.));
+        actor.sub_energy(given);
+        target.add_energy(given);
+        target.clamp_energy();
+    }
+}
+
+/**Emitting a signal driven by its own output neuron*/
+pub struct SignalEffector;
+
+impl Effector for SignalEffector {
+    fn name(&self) -> &'static str {
+        "signal"
+    }
+
+    fn n_actions(&self) -> usize {
+        1
+    }
+
+    fn n_values(&self) -> usize {
+        1
+    }
+
+    fn act(&self, _action: usize, values: &[f32], ctx: &mut ActContext) {
+        ctx.pay(self.name());
+        let centre = ctx.centre();
+        ctx.signals.emit(centre, values[0]);
+    }
+}
+
+/**Leaving scent on the cell at the centre of the body*/
+pub struct DepositEffector;
+
+impl Effector for DepositEffector {
+    fn name(&self) -> &'static str {
+        "deposit"
+    }
+
+    fn n_actions(&self) -> usize {
+        1
+    }
+
+    fn act(&self, _action: usize, _values: &[f32], ctx: &mut ActContext) {
+        ctx.pay(self.name());
+        let centre = ctx.centre();
+        ctx.scent.deposit(centre, ctx.params.scent_deposit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn organism(energy: f32) -> Organism {
+        Organism {
+            energy,
+            ..Organism::new(0., 0, &mut rand::rng())
+        }
+    }
+
+    #[test]
+    fn test_attack() {
+        let params = Parameters {
+            attack_damage: 0.3,
+            predation_efficiency: 0.5,
+            ..Parameters::default()
+        };
+        let (mut actor, mut target) = (organism(0.2), organism(0.5));
+        AttackEffector.interact(0, &mut actor, &mut target, &params);
+        assert!((target.energy - 0.2).abs() < 1e-6);
+        assert!((actor.energy - 0.35).abs() < 1e-6);
+        assert!((actor.gathered - 0.15).abs() < 1e-6);
+
+        //the drain is capped at what the target has left, which kills it
+        let (mut actor, mut target) = (organism(0.2), organism(0.1));
+        AttackEffector.interact(0, &mut actor, &mut target, &params);
+        assert!(target.is_dead());
+        assert!((actor.energy - 0.25).abs() < 1e-6);
+
+        //the actor can't store more than its capacity, nor count what it can't store as gathered
+        let (mut actor, mut target) = (organism(0.95), organism(0.5));
+        AttackEffector.interact(0, &mut actor, &mut target, &params);
+        assert_eq!(actor.max_energy, actor.energy);
+        assert!((actor.gathered - 0.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_share() {
+        let params = Parameters {
+            share_energy: 0.2,
+            ..Parameters::default()
+        };
+        let (mut actor, mut target) = (organism(0.5), organism(0.3));
+        ShareEffector.interact(0, &mut actor, &mut target, &params);
+        assert!((actor.energy - 0.3).abs() < 1e-6);
+        assert!((target.energy - 0.5).abs() < 1e-6);
+
+        //organisms can't give more than they have, nor targets store more than their capacity
+        let (mut actor, mut target) = (organism(0.1), organism(0.95));
+        ShareEffector.interact(0, &mut actor, &mut target, &params);
+        assert_eq!(0., actor.energy);
+        assert!(!actor.is_dead());
+        assert_eq!(target.max_energy, target.energy);
+    }
+}
@@ -1,42 +1,77 @@
+use crate::components::ui::{HeatmapOverlay, ScentOverlay};
 use crate::components::*;
-use crate::resources::Parameters;
+use crate::resources::{Grid, Heatmaps, Parameters, Registry};
+use bevy::asset::RenderAssetUsages;
+use bevy::image::ImageSampler;
 use bevy::prelude::*;
-use bevy::color::palettes::css::GREEN;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use rand::Rng;
 
 pub fn spawn_organism(
     commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<ColorMaterial>>,
-    org: &Organism,
-    coord: &Coord<isize>,
-    color: Color,
-    params: &Parameters,
-) {
-    let dir: Dir = rand::random();
+    (meshes, materials): (&mut Assets<Mesh>, &mut Assets<ColorMaterial>),
+    grid: &mut Grid,
+    (org, coord, color): (&Organism, &Coord<isize>, Color),
+    (params, registry): (&Parameters, &Registry),
+    rng: &mut impl Rng,
+) -> Entity {
+    let dir: Dir = rng.random();
 
-    let (conn_genes, neuron_genes): (Vec<Gene>, Vec<Gene>) =
-        org.genome.iter().partition(|g| g.is_connection());
-
-    let mut neurons: Vec<_> = neuron_genes
-        .into_iter()
-        .map(|g| Neuron::from_gene(g))
-        .collect();
-    neurons.sort_by(|a, b| a.0.cmp(&b.0));
+    let (conn_genes, neurons) = decode_genes(org);
 
-    let (sensor_neurons, hidden_neurons) = neurons.split_at(SensorySystem::N_SENSORS);
+    let (trait_neurons, hidden_neurons) = neurons.split_at(params.n_trait_genes());
+    let (sensor_neurons, trait_neurons) = trait_neurons.split_at(SensorySystem::N_SENSORS);
+    let (morphology_neurons, trait_neurons) = trait_neurons.split_at(Morphology::N_GENES);
+    let (body_neurons, trait_neurons) = trait_neurons.split_at(Body::N_GENES);
+    let (digestion_neurons, oscillator_neurons) =
+        trait_neurons.split_at(trait_neurons.len() - params.n_oscillator_genes());
 
     let ss = SensorySystem::new(
         sensor_neurons
             .into_iter()
             .map(|(_, _, neuron)| (neuron.w - 0.5) * 2.)
             .collect(),
+        Morphology::from_weights(
+            &morphology_neurons
+                .iter()
+                .map(|(_, _, neuron)| neuron.w)
+                .collect::<Vec<f32>>(),
+            params.max_sensor_range,
+        ),
+        params.food.len(),
+    );
+
+    let body = Body::from_weights(
+        &body_neurons
+            .iter()
+            .map(|(_, _, neuron)| neuron.w)
+            .collect::<Vec<f32>>(),
+        params.max_body_size,
+        params.max_speed,
+        params.lifespan,
     );
 
-    let ns_shape = NsShape::new(
-        SensorySystem::N_SENSORS,
-        hidden_neurons.len(),
-        Action::N_ACTIONS,
+    let digestion = if params.digestion {
+        Digestion::from_weights(
+            digestion_neurons
+                .iter()
+                .map(|(_, _, neuron)| neuron.w)
+                .collect(),
+        )
+    } else {
+        Digestion::uniform(params.food.len())
+    };
+
+    let proprioception = Proprioception::new(
+        &params.internal_sensors,
+        oscillator_neurons
+            .iter()
+            .map(|(_, _, neuron)| neuron.w)
+            .collect(),
+        registry.n_actions(),
     );
+
+    let ns_shape = registry.ns_shape(params, hidden_neurons.len());
     let ns = NeuralSystem::new(
         &hidden_neurons
             .into_iter()
@@ -49,20 +84,85 @@ pub fn spawn_organism(
         ns_shape,
     );
 
-    commands.spawn((
-        org.to_owned(),
-        ss,
-        ns,
-        coord.to_owned(),
-        dir,
-        Mesh2d(meshes.add(Rectangle::new(params.cell_width, params.cell_width))),
-        MeshMaterial2d(materials.add(ColorMaterial::from(color))),
-        Transform::from_translation(Vec3::new(
-            (coord.x as f32) * params.cell_width,
-            (coord.y as f32) * params.cell_height,
-            0.,
-        )),
-    ));
+    let metabolism = basal_metabolism(org, &body, &ss, &ns, params);
+
+    let mut org = org.to_owned();
+    org.max_energy = body.capacity();
+    let species = org.species;
+
+    let entity = commands
+        .spawn((
+            org,
+            body,
+            ss,
+            ns,
+            digestion,
+            proprioception,
+            metabolism,
+            coord.to_owned(),
+            dir,
+            Mesh2d(meshes.add(Rectangle::new(
+                params.cell_width * body.size as f32,
+                params.cell_height * body.size as f32,
+            ))),
+            MeshMaterial2d(materials.add(ColorMaterial::from(color))),
+            Transform::from_translation(Vec3::new(
+                (coord.x as f32 + body.offset()) * params.cell_width,
+                (coord.y as f32 + body.offset()) * params.cell_height,
+                0.,
+            )),
+        ))
+        .id();
+
+    grid.set_footprint(*coord, body.size, CellType::Occupied(entity, species));
+
+    entity
+}
+
+/**Metabolism with the upkeep the phenotype costs under the current parameters*/
+pub fn basal_metabolism(
+    org: &Organism,
+    body: &Body,
+    ss: &SensorySystem,
+    ns: &NeuralSystem,
+    params: &Parameters,
+) -> Metabolism {
+    Metabolism::new(
+        params.neuron_cost * ns.n_neurons() as f32 + params.edge_cost * ns.n_edges() as f32,
+        params.gene_cost * org.genome.n_genes() as f32,
+        params.vision_cost * ss.morphology.size(params.max_sensor_range),
+        params.body_cost * (body.n_cells() * body.speed) as f32,
+        params.longevity_cost * body.lifespan as f32 / params.lifespan.max(1) as f32,
+    )
+}
+
+/**Connection genes and neuron genes sorted by index, the first of which encode traits*/
+fn decode_genes(org: &Organism) -> (Vec<Gene>, Vec<(usize, bool, Neuron)>) {
+    let (conn_genes, neuron_genes): (Vec<Gene>, Vec<Gene>) =
+        org.genome.iter().partition(|g| g.is_connection());
+
+    let mut neurons: Vec<_> = neuron_genes
+        .into_iter()
+        .map(|g| Neuron::from_gene(g))
+        .collect();
+    neurons.sort_by(|a, b| a.0.cmp(&b.0));
+
+    (conn_genes, neurons)
+}
+
+/**Body the organism will grow on spawn, to find room for it beforehand*/
+pub fn decode_body(org: &Organism, params: &Parameters) -> Body {
+    let (_, neurons) = decode_genes(org);
+    let offset = SensorySystem::N_SENSORS + Morphology::N_GENES;
+    Body::from_weights(
+        &neurons[offset..offset + Body::N_GENES]
+            .iter()
+            .map(|(_, _, neuron)| neuron.w)
+            .collect::<Vec<f32>>(),
+        params.max_body_size,
+        params.max_speed,
+        params.lifespan,
+    )
 }
 
 pub fn spawn_pellet(
@@ -70,18 +170,97 @@ pub fn spawn_pellet(
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<ColorMaterial>>,
     coord: &Coord<isize>,
-    cell_width: f32,
-    cell_height: f32,
+    kind: usize,
+    params: &Parameters,
 ) {
+    let color = params.food[kind].color;
+
     commands.spawn((
         Pellet,
         coord.to_owned(),
-        Mesh2d(meshes.add(Rectangle::new(cell_width, cell_width))),
-        MeshMaterial2d(materials.add(ColorMaterial::from(Color::Srgba(GREEN)))),
+        Mesh2d(meshes.add(Rectangle::new(params.cell_width, params.cell_height))),
+        MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb_u8(
+            color.0, color.1, color.2,
+        )))),
         Transform::from_translation(Vec3::new(
-            (coord.x as f32) * cell_width,
-            (coord.y as f32) * cell_height,
+            (coord.x as f32) * params.cell_width,
+            (coord.y as f32) * params.cell_height,
             0.,
         )),
     ));
 }
+
+/**Hidden sprite covering the grid with one texel per cell*/
+pub fn spawn_scent_overlay(
+    commands: &mut Commands,
+    images: &mut ResMut<Assets<Image>>,
+    params: &Parameters,
+) {
+    let (sprite, transform) = overlay_sprite(
+        images,
+        (params.grid_width, params.grid_height),
+        1,
+        1.,
+        params,
+    );
+    commands.spawn((ScentOverlay, sprite, transform, Visibility::Hidden));
+}
+
+/**Hidden sprite covering the grid with one texel per heatmap region, drawn over the scent overlay*/
+pub fn spawn_heatmap_overlay(
+    commands: &mut Commands,
+    images: &mut ResMut<Assets<Image>>,
+    heatmaps: &Heatmaps,
+    params: &Parameters,
+) {
+    let (sprite, transform) =
+        overlay_sprite(images, heatmaps.shape(), heatmaps.resolution(), 2., params);
+    commands.spawn((
+        HeatmapOverlay::default(),
+        sprite,
+        transform,
+        Visibility::Hidden,
+    ));
+}
+
+/**Transparent RGBA texture of shape texels, sampled without blending*/
+pub fn blank_image(shape: (usize, usize)) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: shape.0 as u32,
+            height: shape.1 as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+/**Sprite of a transparent texture of shape texels, each covering resolution cells per side from the grid origin*/
+fn overlay_sprite(
+    images: &mut ResMut<Assets<Image>>,
+    shape: (usize, usize),
+    resolution: usize,
+    z: f32,
+    params: &Parameters,
+) -> (Sprite, Transform) {
+    let width = (shape.0 * resolution) as f32 * params.cell_width;
+    let height = (shape.1 * resolution) as f32 * params.cell_height;
+
+    (
+        Sprite {
+            image: images.add(blank_image(shape)),
+            custom_size: Some(Vec2::new(width, height)),
+            ..default()
+        },
+        Transform::from_translation(Vec3::new(
+            (width - params.cell_width) * 0.5,
+            (height - params.cell_height) * 0.5,
+            z,
+        )),
+    )
+}
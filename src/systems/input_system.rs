@@ -1,16 +1,42 @@
+use crate::components::ui::{HeatmapOverlay, ParameterPanel, PhylogenyPanel, ScentOverlay};
 use crate::resources::*;
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 use std::time::Duration;
 
+/**Bounds of the camera scale, above 1 more than the whole world is in view*/
+const MIN_ZOOM: f32 = 0.02;
+const MAX_ZOOM: f32 = 2.;
+
+/**Filters keeping the visibilities toggled from the keyboard apart*/
+type PhylogenyPanelOnly = (With<PhylogenyPanel>, Without<ScentOverlay>);
+type ParameterPanelOnly = (
+    With<ParameterPanel>,
+    Without<ScentOverlay>,
+    Without<PhylogenyPanel>,
+);
+type HeatmapOnly = (
+    Without<ScentOverlay>,
+    Without<PhylogenyPanel>,
+    Without<ParameterPanel>,
+);
+
 pub fn input_system(
     mut camera_query: Query<&mut Transform, With<Camera>>,
-    keys: Res<ButtonInput<KeyCode>>,
-    mut scroll_events: MessageReader<MouseWheel>,
-    time: Res<Time>,
-    mut sim_time: ResMut<SimTime>,
-    mut epoch_time: ResMut<EpochTime>,
-    mut sim_state: ResMut<SimState>,
+    (keys, mut scroll_events, time): (
+        Res<ButtonInput<KeyCode>>,
+        MessageReader<MouseWheel>,
+        Res<Time>,
+    ),
+    (mut sim_time, mut epoch_time, mut sim_state): (
+        ResMut<SimTime>,
+        ResMut<EpochTime>,
+        ResMut<SimState>,
+    ),
+    mut overlay: Single<&mut Visibility, With<ScentOverlay>>,
+    mut phylogeny_panel: Single<&mut Visibility, PhylogenyPanelOnly>,
+    mut parameter_panel: Single<&mut Visibility, ParameterPanelOnly>,
+    mut heatmap: Single<(&mut Visibility, &mut HeatmapOverlay), HeatmapOnly>,
 ) {
     if keys.just_pressed(KeyCode::Space) {
         sim_state.paused ^= true;
@@ -18,6 +44,32 @@ pub fn input_system(
     if keys.just_pressed(KeyCode::KeyR) {
         sim_state.reset = true;
     }
+    if keys.just_pressed(KeyCode::KeyO) {
+        **overlay = match **overlay {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+    if keys.just_pressed(KeyCode::KeyH) {
+        let (visibility, overlay) = &mut *heatmap;
+        overlay.0 = HeatmapLayer::next(overlay.0);
+        **visibility = match overlay.0 {
+            Some(_) => Visibility::Visible,
+            None => Visibility::Hidden,
+        };
+    }
+    if keys.just_pressed(KeyCode::KeyT) {
+        **phylogeny_panel = match **phylogeny_panel {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+    if keys.just_pressed(KeyCode::KeyP) {
+        **parameter_panel = match **parameter_panel {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
 
     //sim speed control
     if keys.just_pressed(KeyCode::Digit1) {
@@ -43,21 +95,12 @@ pub fn input_system(
         };
         log_scale -= 5. * ev.y.signum() * time.delta_secs();
         camera.scale = log_scale.exp();
-        camera.scale = camera.scale.clamp(
-            Vec3 {
-                x: 0.,
-                y: 0.,
-                z: 0.,
-            },
-            Vec3 {
-                x: 1.,
-                y: 1.,
-                z: 1.,
-            },
-        );
+        camera.scale = camera
+            .scale
+            .clamp(Vec3::splat(MIN_ZOOM), Vec3::splat(MAX_ZOOM));
     }
 
-    let scale_factor = 1.2 - camera.scale.x;
+    let scale_factor = (1.2 - camera.scale.x).max(0.2);
     let delta = 5e2 * scale_factor * time.delta_secs();
     let mut camera_delta_y = 0.;
     let mut camera_delta_x = 0.;
@@ -0,0 +1,97 @@
+use crate::components::*;
+use crate::resources::*;
+
+/**Vision of the cells in front of the organism using the configured perception model*/
+pub struct VisionSensor;
+
+impl Sensor for VisionSensor {
+    fn n_inputs(&self, params: &Parameters, _n_actions: usize) -> usize {
+        match params.vision {
+            Vision::Columns => SensorySystem::n_inputs(params.food.len()),
+            Vision::Rays => SensorySystem::n_ray_inputs(params.food.len()),
+        }
+    }
+
+    fn sense(&self, ctx: &SenseContext, inputs: &mut Vec<f32>) {
+        let perceive = match ctx.params.vision {
+            Vision::Columns => SensorySystem::process_data,
+            Vision::Rays => SensorySystem::cast_rays,
+        };
+        let observer = Observer {
+            entity: ctx.entity,
+            species: ctx.org.species,
+            kinship: ctx
+                .params
+                .kin_distance
+                .map(|threshold| (&ctx.org.genome, ctx.genomes, threshold)),
+        };
+        inputs.extend(perceive(
+            ctx.ss,
+            ctx.grid,
+            ctx.centre(),
+            ctx.dir,
+            &observer,
+            ctx.environment.sensor_range,
+        ));
+    }
+}
+
+/**Daylight and food abundance*/
+pub struct EnvironmentSensor;
+
+impl Sensor for EnvironmentSensor {
+    fn n_inputs(&self, _params: &Parameters, _n_actions: usize) -> usize {
+        Environment::N_INPUTS
+    }
+
+    fn sense(&self, ctx: &SenseContext, inputs: &mut Vec<f32>) {
+        inputs.extend(ctx.environment.inputs());
+    }
+}
+
+/**Signals emitted by neighbours during the last tick*/
+pub struct SignalSensor;
+
+impl Sensor for SignalSensor {
+    fn n_inputs(&self, _params: &Parameters, _n_actions: usize) -> usize {
+        Signals::N_INPUTS
+    }
+
+    fn sense(&self, ctx: &SenseContext, inputs: &mut Vec<f32>) {
+        inputs.extend(
+            ctx.signals
+                .receive(ctx.grid, ctx.centre(), ctx.params.signal_range),
+        );
+    }
+}
+
+/**Scent intensity and gradients*/
+pub struct ScentSensor;
+
+impl Sensor for ScentSensor {
+    fn n_inputs(&self, params: &Parameters, _n_actions: usize) -> usize {
+        ScentField::n_inputs(params.food.len())
+    }
+
+    fn sense(&self, ctx: &SenseContext, inputs: &mut Vec<f32>) {
+        inputs.extend(ctx.scent.sense(ctx.grid, ctx.centre(), ctx.dir));
+    }
+}
+
+/**Configured internal state sensors*/
+pub struct ProprioceptionSensor;
+
+impl Sensor for ProprioceptionSensor {
+    fn n_inputs(&self, params: &Parameters, n_actions: usize) -> usize {
+        Proprioception::n_inputs(&params.internal_sensors, n_actions)
+    }
+
+    fn sense(&self, ctx: &SenseContext, inputs: &mut Vec<f32>) {
+        inputs.extend(ctx.proprioception.process_data(
+            ctx.org,
+            ctx.dir,
+            ctx.body.lifespan,
+            ctx.tick,
+        ));
+    }
+}
@@ -1,11 +1,21 @@
+mod energy_ledger_system;
 mod energy_system;
 mod epoch_system;
 mod init_system;
+mod metrics_system;
+mod parameters_system;
+mod phylogeny_system;
 mod reset_system;
 mod sim_step_system;
+mod speciation_system;
 
+pub use energy_ledger_system::*;
 pub use energy_system::*;
 pub use epoch_system::*;
 pub use init_system::*;
+pub use metrics_system::*;
+pub use parameters_system::*;
+pub use phylogeny_system::*;
 pub use reset_system::*;
 pub use sim_step_system::*;
+pub use speciation_system::*;
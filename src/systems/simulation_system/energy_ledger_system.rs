@@ -0,0 +1,62 @@
+use crate::components::Organism;
+use crate::resources::*;
+use bevy::prelude::*;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+pub fn energy_ledger_system(
+    params: Res<Parameters>,
+    grid: Res<Grid>,
+    sim_state: Res<SimState>,
+    orgs_query: Query<&Organism>,
+    mut ledger: ResMut<EnergyLedger>,
+) {
+    let total_org_energy = orgs_query.iter().map(|org| org.energy as f64).sum::<f64>();
+    let total_pellet_energy = grid
+        .get_food_coords()
+        .iter()
+        .map(|(_, kind)| params.food[*kind].energy as f64)
+        .sum::<f64>();
+
+    if let Some(discrepancy) = ledger.audit(total_org_energy + total_pellet_energy) {
+        warn!(
+            "Unaccounted energy change of {:.4} at tick {}",
+            discrepancy, sim_state.tick
+        );
+    }
+
+    if sim_state.epoch != ledger.epoch {
+        let epoch = ledger.epoch;
+        let flows = ledger.close_epoch(sim_state.epoch);
+
+        if let Some(path) = &params.ledger_log
+            && let Err(e) = append_log(path, epoch, flows.iter())
+        {
+            warn!("Failed to write energy ledger to {}: {}", path, e);
+        }
+    }
+}
+
+/**Append one epoch,cause,energy line per flow*/
+fn append_log<'a>(
+    path: &str,
+    epoch: usize,
+    flows: impl Iterator<Item = (&'a &'static str, &'a f64)>,
+) -> std::io::Result<()> {
+    let path = Path::new(path);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let is_new = !path.exists();
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if is_new {
+        writeln!(file, "epoch,cause,energy")?;
+    }
+    for (cause, energy) in flows {
+        writeln!(file, "{},{},{}", epoch, cause, energy)?;
+    }
+
+    Ok(())
+}
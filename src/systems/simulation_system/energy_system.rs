@@ -1,21 +1,81 @@
 use crate::components::*;
-use crate::resources::Grid;
+use crate::resources::{Environment, FoodField, Grid, Parameters};
+use rand::Rng;
 use rand::seq::IndexedRandom;
+use std::collections::{HashMap, HashSet};
 
 const MAX_ENERGY: f32 = 100.;
 
-pub fn energy_system(total_org_energy: f32, grid: &Grid) -> Vec<Coord<isize>> {
-    let rng = &mut rand::rng();
-    let total_pellet_energy =
-        grid.get_cell_coords(CellType::Consumable).iter().len() as f32 * PELLET_ENERGY;
+pub fn energy_system(
+    total_org_energy: f32,
+    grid: &Grid,
+    food_field: &FoodField,
+    environment: &Environment,
+    params: &Parameters,
+    rng: &mut impl Rng,
+) -> Vec<(Coord<isize>, usize)> {
+    let food_coords = grid.get_food_coords();
+    let total_pellet_energy = food_coords
+        .iter()
+        .map(|(_, kind)| params.food[*kind].energy)
+        .sum::<f32>();
     let total_energy = total_org_energy + total_pellet_energy;
-    let n_pellets = (((MAX_ENERGY - total_energy) * 0.05) / PELLET_ENERGY) as usize;
-    if n_pellets <= 0 {
-        return Vec::<Coord<isize>>::new();
+    let supply_energy =
+        (MAX_ENERGY - total_energy) * params.supply_rate * environment.food_abundance;
+    let total_supply = params.food.iter().map(|kind| kind.supply).sum::<f32>();
+    if supply_energy <= 0. || total_supply <= 0. {
+        return Vec::<(Coord<isize>, usize)>::new();
     }
 
-    grid.get_cell_coords(CellType::Empty)
-        .choose_multiple(rng, n_pellets)
-        .cloned()
-        .collect::<Vec<Coord<isize>>>()
+    let empty_coords = grid.get_cell_coords(CellType::Empty);
+    let mut taken = HashSet::<Coord<isize>>::new();
+    let mut res = Vec::<(Coord<isize>, usize)>::new();
+
+    for (kind, food_kind) in params.food.iter().enumerate() {
+        let n_pellets =
+            (supply_energy * food_kind.supply / total_supply / food_kind.energy) as usize;
+        if n_pellets == 0 {
+            continue;
+        }
+
+        //plants spread into empty cells next to existing food of the same kind
+        let mut n_neighbors = HashMap::<Coord<isize>, usize>::new();
+        for (coord, _) in food_coords.iter().filter(|(_, k)| *k == kind) {
+            for neighbor in grid.search_area(*coord, 1, CellType::Empty) {
+                if !taken.contains(&neighbor) {
+                    *n_neighbors.entry(neighbor).or_default() += 1;
+                }
+            }
+        }
+
+        let n_grown = ((n_pellets as f32 * food_kind.growth) as usize).min(n_neighbors.len());
+        //sorted so that the same draws pick the same cells
+        let mut candidates: Vec<_> = n_neighbors.iter().collect();
+        candidates.sort_by_key(|(coord, _)| (coord.x, coord.y));
+        let grown: Vec<Coord<isize>> = candidates
+            .choose_multiple_weighted(rng, n_grown, |(coord, n)| {
+                **n as f32 * food_field.get(kind, coord.x as usize, coord.y as usize)
+            })
+            .unwrap()
+            .map(|(coord, _)| **coord)
+            .collect();
+        taken.extend(grown.iter().cloned());
+
+        //remaining pellets are seeded according to fertility
+        let seeded = empty_coords
+            .iter()
+            .filter(|coord| !taken.contains(coord))
+            .collect::<Vec<_>>()
+            .choose_multiple_weighted(rng, n_pellets - grown.len(), |coord| {
+                food_field.get(kind, coord.x as usize, coord.y as usize)
+            })
+            .unwrap()
+            .map(|coord| **coord)
+            .collect::<Vec<Coord<isize>>>();
+        taken.extend(seeded.iter().cloned());
+
+        res.extend(grown.into_iter().chain(seeded).map(|coord| (coord, kind)));
+    }
+
+    res
 }
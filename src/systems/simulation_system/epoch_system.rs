@@ -1,4 +1,4 @@
-use crate::components::{Coord, Organism};
+use crate::components::{Body, Coord, Organism};
 use crate::resources::*;
 use crate::systems::*;
 use bevy::prelude::*;
@@ -6,19 +6,26 @@ use rand::Rng;
 
 pub fn epoch_system(
     mut commands: Commands,
-    time: Res<Time>,
-    mut sim_state: ResMut<SimState>,
-    params: Res<Parameters>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut epoch_time: ResMut<EpochTime>,
-    mut grid: ResMut<Grid>,
-    mut species: ResMut<Species>,
-    mut orgs_query: Query<(Entity, &mut Organism, &Coord<isize>)>,
+    (time, mut sim_state, mut epoch_time): (Res<Time>, ResMut<SimState>, ResMut<EpochTime>),
+    (params, mut rng): (Res<Parameters>, ResMut<SimRng>),
+    (mut meshes, mut materials): (ResMut<Assets<Mesh>>, ResMut<Assets<ColorMaterial>>),
+    (mut grid, mut food_field, environment, mut species): (
+        ResMut<Grid>,
+        ResMut<FoodField>,
+        Res<Environment>,
+        ResMut<Species>,
+    ),
+    (mut ledger, mut genealogy, mut metrics, mut heatmaps): (
+        ResMut<EnergyLedger>,
+        ResMut<Genealogy>,
+        ResMut<Metrics>,
+        ResMut<Heatmaps>,
+    ),
+    mut orgs_query: Query<(Entity, &mut Organism, &Body, &Coord<isize>)>,
 ) {
     if !sim_state.paused && !sim_state.reset && epoch_time.timer.tick(time.delta()).just_finished()
     {
-        let mut rng = rand::rng();
+        let rng = &mut rng.0;
         let mut total_orgs_energy: f32 = 0.;
 
         sim_state.epoch += 1;
@@ -26,15 +33,19 @@ pub fn epoch_system(
         let mut n_entities = orgs_query.iter().len();
 
         //organism death
-        for (e, mut org, coord) in orgs_query.iter_mut() {
+        for (e, mut org, body, coord) in orgs_query.iter_mut() {
             org.age += 1;
 
-            if org.age > params.lifespan
-                && rng.random_bool((org.age as f64 / params.lifespan as f64 - 1.).clamp(0., 1.))
+            if org.age > body.lifespan
+                && rng.random_bool((org.age as f64 / body.lifespan as f64 - 1.).clamp(0., 1.))
             {
                 n_entities -= 1;
-                grid.set(coord.x as usize, coord.y as usize, CellType::Empty);
+                grid.set_footprint(*coord, body.size, CellType::Empty);
                 species.decrement_species(org.species);
+                ledger.record("death", -org.energy);
+                genealogy.record(&org, sim_state.tick, "old_age");
+                metrics.death("old_age");
+                heatmaps.record(HeatmapLayer::Deaths, *coord);
 
                 commands.entity(e).despawn();
                 continue;
@@ -43,20 +54,32 @@ pub fn epoch_system(
             }
         }
 
+        genealogy.flush();
+
         if n_entities == 0 {
             return;
         }
 
-        let pellet_coords = energy_system(total_orgs_energy, &grid);
-        for coord in pellet_coords.iter() {
-            grid.set(coord.x as usize, coord.y as usize, CellType::Consumable);
+        food_field.update(rng);
+        food_field.set_shift(environment.fertility_shift);
+        let pellet_coords = energy_system(
+            total_orgs_energy,
+            &grid,
+            &food_field,
+            &environment,
+            &params,
+            rng,
+        );
+        for (coord, kind) in pellet_coords.iter() {
+            grid.set(coord.x as usize, coord.y as usize, CellType::Consumable(*kind));
+            ledger.record("supply", params.food[*kind].energy);
             spawn_pellet(
                 &mut commands,
                 &mut meshes,
                 &mut materials,
                 coord,
-                params.cell_width,
-                params.cell_height,
+                *kind,
+                &params,
             );
         }
     }
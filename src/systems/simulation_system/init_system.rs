@@ -1,43 +1,61 @@
-use crate::components::{CellType, Coord, Organism, SensorySystem};
+use crate::components::{CellType, Coord, Organism};
 use crate::resources::{Grid, Parameters, Species};
+use crate::systems::decode_body;
+use bevy::prelude::Entity;
 use rand::Rng;
 
 const INITIAL_ENERGY: f32 = 0.2;
 
-pub fn init_system(params: Parameters) -> (Vec<Organism>, Species, Vec<Coord<isize>>, Grid) {
+pub fn init_system(
+    params: &Parameters,
+    rng: &mut impl Rng,
+) -> (Vec<Organism>, Species, Vec<Coord<isize>>, Grid) {
     let mut orgs = Vec::<Organism>::with_capacity(params.n_initial_entities * 3);
     let mut coords = Vec::<Coord<isize>>::with_capacity(params.n_initial_entities * 3);
 
-    let mut grid = Grid::new((params.grid_size, params.grid_size));
-
-    let mut rng = rand::rng();
+    let mut grid = Grid::new((params.grid_width, params.grid_height), params.topology);
 
     let mut n = 0;
     while n < params.n_initial_entities {
-        let x = rng.random_range(0..params.grid_size);
-        let y = rng.random_range(0..params.grid_size);
+        let coord = Coord::<isize> {
+            x: rng.random_range(0..params.grid_width) as isize,
+            y: rng.random_range(0..params.grid_height) as isize,
+        };
 
-        if grid.get(x, y) == CellType::Impassable {
+        let mut org = Organism::new(
+            INITIAL_ENERGY,
+            params.n_initial_connections + params.n_initial_neurons + params.n_trait_genes(),
+            rng,
+        );
+        org.genome.set_gene_types(
+            params.n_initial_connections,
+            params.n_initial_neurons + params.n_trait_genes(),
+        );
+
+        let size = decode_body(&org, params).size;
+        let Some(cells) = grid.footprint(coord, size) else {
+            continue;
+        };
+        if cells
+            .iter()
+            .any(|c| !grid.get(c.x as usize, c.y as usize).is_passable())
+        {
             continue;
         }
 
-        grid.set(x, y, CellType::Impassable);
-
-        orgs.push(Organism::new(
-            INITIAL_ENERGY,
-            params.n_initial_connections + params.n_initial_neurons + SensorySystem::N_SENSORS,
-        ));
+        //entity is assigned on spawn
+        grid.set_footprint(coord, size, CellType::Occupied(Entity::PLACEHOLDER, 0));
 
-        let coord = Coord::<isize> {
-            x: x as isize,
-            y: y as isize,
-        };
+        orgs.push(org);
         coords.push(coord);
 
         n += 1;
     }
     let (species, assigned_species) =
-        Species::from_genomes(&(orgs.iter().map(|o| &o.genome).collect()), 1e-1);
+        Species::from_genomes(
+        &(orgs.iter().map(|o| &o.genome).collect()),
+        params.speciation_threshold,
+    );
     orgs.iter_mut()
         .zip(assigned_species.iter())
         .for_each(|(org, species)| org.species = *species);
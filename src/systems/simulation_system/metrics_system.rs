@@ -0,0 +1,41 @@
+use crate::components::{NeuralSystem, Organism};
+use crate::resources::*;
+use bevy::prelude::*;
+
+pub fn metrics_system(
+    params: Res<Parameters>,
+    grid: Res<Grid>,
+    sim_state: Res<SimState>,
+    species: Res<Species>,
+    mut metrics: ResMut<Metrics>,
+    orgs_query: Query<(&Organism, &NeuralSystem)>,
+) {
+    if sim_state.epoch == metrics.epoch {
+        return;
+    }
+
+    let population = orgs_query.iter().len();
+    let mean = |total: usize| total as f32 / population.max(1) as f32;
+
+    let row = MetricsRow {
+        tick: sim_state.tick,
+        population,
+        n_species: species.len(),
+        org_energy: orgs_query.iter().map(|(org, _)| org.energy).sum(),
+        pellet_energy: grid
+            .get_food_coords()
+            .iter()
+            .map(|(_, kind)| params.food[*kind].energy)
+            .sum(),
+        mean_genome_len: mean(orgs_query.iter().map(|(org, _)| org.genome.n_genes()).sum()),
+        max_genome_len: orgs_query
+            .iter()
+            .map(|(org, _)| org.genome.n_genes())
+            .max()
+            .unwrap_or_default(),
+        mean_hidden_neurons: mean(orgs_query.iter().map(|(_, ns)| ns.n_hidden()).sum()),
+        mean_edges: mean(orgs_query.iter().map(|(_, ns)| ns.n_edges()).sum()),
+        ..default()
+    };
+    metrics.close_epoch(sim_state.epoch, row);
+}
@@ -0,0 +1,46 @@
+use crate::components::*;
+use crate::resources::*;
+use crate::systems::{basal_metabolism, decode_body};
+use bevy::prelude::*;
+
+/**Apply edited parameters to living organisms, the speciation threshold and the tick duration*/
+pub fn parameters_system(
+    params: Res<Parameters>,
+    mut species: ResMut<Species>,
+    (mut sim_time, mut epoch_time): (ResMut<SimTime>, ResMut<EpochTime>),
+    mut orgs_query: Query<(
+        &Organism,
+        &mut Body,
+        &SensorySystem,
+        &NeuralSystem,
+        &mut Metabolism,
+    )>,
+    mut applied: Local<Option<(f32, f32)>>,
+) {
+    let Some((threshold, tick_seconds)) = *applied else {
+        //organisms were spawned with the initial parameters
+        *applied = Some((params.speciation_threshold, params.tick_seconds));
+        return;
+    };
+
+    if threshold != params.speciation_threshold {
+        species.threshold = params.speciation_threshold;
+    }
+    if tick_seconds != params.tick_seconds {
+        sim_time.set_tick_duration(&mut epoch_time, params.tick_seconds);
+    }
+
+    for (org, mut body, ss, ns, mut metabolism) in orgs_query.iter_mut() {
+        //bodies keep the size they occupy the grid with
+        *body = Body {
+            size: body.size,
+            ..decode_body(org, &params)
+        };
+
+        let spent = std::mem::take(&mut metabolism.spent);
+        *metabolism = basal_metabolism(org, &body, ss, ns, &params);
+        metabolism.spent = spent;
+    }
+
+    *applied = Some((params.speciation_threshold, params.tick_seconds));
+}
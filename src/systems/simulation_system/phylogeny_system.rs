@@ -0,0 +1,32 @@
+use crate::resources::*;
+use bevy::prelude::*;
+use std::fs;
+use std::path::Path;
+
+pub fn phylogeny_system(
+    params: Res<Parameters>,
+    sim_state: Res<SimState>,
+    species: Res<Species>,
+    mut phylogeny: ResMut<Phylogeny>,
+) {
+    let new_epoch = sim_state.epoch != phylogeny.epoch;
+    phylogeny.update(&species, sim_state.epoch);
+
+    if new_epoch
+        && let Some(path) = &params.phylogeny_log
+        && let Err(e) = export(path, &phylogeny)
+    {
+        warn!("Failed to write phylogeny to {}: {}", path, e);
+    }
+}
+
+/**Overwrite the Newick and JSON files with the current tree*/
+fn export(path: &str, phylogeny: &Phylogeny) -> std::io::Result<()> {
+    let path = Path::new(path);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path.with_extension("nwk"), phylogeny.to_newick())?;
+    fs::write(path.with_extension("json"), phylogeny.to_json())
+}
@@ -4,39 +4,48 @@ use crate::systems::*;
 use bevy::prelude::*;
 
 pub fn reset_system(
-    params: Res<Parameters>,
+    (params, registry, mut rng): (Res<Parameters>, Res<Registry>, ResMut<SimRng>),
     mut sim_state: ResMut<SimState>,
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut species: ResMut<Species>,
+    (mut meshes, mut materials): (ResMut<Assets<Mesh>>, ResMut<Assets<ColorMaterial>>),
+    (mut species, mut ledger, mut genealogy, mut metrics): (
+        ResMut<Species>,
+        ResMut<EnergyLedger>,
+        ResMut<Genealogy>,
+        ResMut<Metrics>,
+    ),
     orgs_query: Query<(Entity, &Organism)>,
     pellets_query: Query<(Entity, &Pellet)>,
 ) {
     if sim_state.reset {
-        for (e, _) in orgs_query.iter() {
+        let rng = &mut rng.0;
+        for (e, org) in orgs_query.iter() {
+            genealogy.record(org, sim_state.tick, "reset");
             commands.entity(e).despawn();
         }
+        genealogy.flush();
         commands.remove_resource::<Grid>();
 
-        let (mut orgs, new_species, coords, mut grid) = init_system(*params);
-        orgs.iter_mut().for_each(|o| {
-            o.genome.set_gene_types(
-                params.n_initial_connections,
-                params.n_initial_neurons + SensorySystem::N_SENSORS,
-            )
-        });
+        let (mut orgs, new_species, coords, mut grid) = init_system(&params, rng);
+        orgs.iter_mut().for_each(|org| org.id = genealogy.next_id());
+        let food_field = FoodField::new(
+            &params.food,
+            (params.grid_width, params.grid_height),
+            rng,
+        );
+        let environment = Environment::new(&params.schedule, params.max_sensor_range);
 
         *species = new_species;
+        ledger.reset();
+        metrics.reset();
         for (org, coord) in orgs.iter().zip(coords.iter()) {
             spawn_organism(
                 &mut commands,
-                &mut meshes,
-                &mut materials,
-                org,
-                coord,
-                species.get_color(org.species),
-                &params,
+                (&mut meshes, &mut materials),
+                &mut grid,
+                (org, coord, species.get_color(org.species)),
+                (&params, &registry),
+                rng,
             );
         }
 
@@ -44,22 +53,42 @@ pub fn reset_system(
             commands.entity(e).despawn();
         }
 
-        let pellet_coords = energy_system(orgs.iter().map(|org| org.energy).sum::<f32>(), &grid);
-        for coord in pellet_coords {
-            grid.set(coord.x as usize, coord.y as usize, CellType::Consumable);
+        let pellet_coords = energy_system(
+            orgs.iter().map(|org| org.energy).sum::<f32>(),
+            &grid,
+            &food_field,
+            &environment,
+            &params,
+            rng,
+        );
+        for (coord, kind) in pellet_coords.iter() {
+            grid.set(coord.x as usize, coord.y as usize, CellType::Consumable(*kind));
             spawn_pellet(
                 &mut commands,
                 &mut meshes,
                 &mut materials,
-                &coord,
-                params.cell_width,
-                params.cell_height,
+                coord,
+                *kind,
+                &params,
             );
         }
 
         commands.insert_resource(grid);
+        commands.insert_resource(food_field);
+        commands.insert_resource(environment);
+        commands.insert_resource(ScentField::new(
+            params.food.len(),
+            (params.grid_width, params.grid_height),
+            params.topology,
+        ));
+        commands.insert_resource(Signals::new((params.grid_width, params.grid_height)));
+        commands.insert_resource(Heatmaps::new(&params));
+        commands.insert_resource(Phylogeny::founders(
+            orgs.iter().map(|org| (org.species, &org.genome)),
+        ));
 
         sim_state.epoch = 0;
+        sim_state.tick = 0;
         sim_state.reset = false;
     }
 }
@@ -1,109 +1,247 @@
-use crate::components::{Action, Coord, Organism};
+use crate::components::{Coord, Genome, Organism};
 use crate::resources::*;
 use crate::systems::*;
 use bevy::prelude::*;
 use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/**Organism with every component a tick reads or changes*/
+type SimulatedOrganism<'a> = (
+    Entity,
+    &'a mut Organism,
+    &'a Body,
+    &'a SensorySystem,
+    &'a mut NeuralSystem,
+    &'a Digestion,
+    &'a mut Coord<isize>,
+    &'a mut Dir,
+    &'a mut Transform,
+    &'a mut Proprioception,
+    &'a mut Metabolism,
+);
+
+/**Pellets, told apart from organisms which also have coordinates*/
+type PelletFilter = (With<Pellet>, Without<Organism>);
 
 pub fn sim_step_system(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    time: Res<Time>,
-    sim_state: Res<SimState>,
-    params: Res<Parameters>,
-    mut sim_time: ResMut<SimTime>,
-    mut grid: ResMut<Grid>,
-    mut species: ResMut<Species>,
-    mut orgs_query: Query<(
-        Entity,
-        &mut Organism,
-        &SensorySystem,
-        &mut NeuralSystem,
-        &mut Coord<isize>,
-        &mut Dir,
-        &mut Transform,
-    )>,
-    pellets_query: Query<(Entity, &Coord<isize>), (With<Pellet>, Without<Organism>)>,
+    (mut commands, mut meshes, mut materials): (
+        Commands,
+        ResMut<Assets<Mesh>>,
+        ResMut<Assets<ColorMaterial>>,
+    ),
+    (time, mut sim_state, mut sim_time, mut environment): (
+        Res<Time>,
+        ResMut<SimState>,
+        ResMut<SimTime>,
+        ResMut<Environment>,
+    ),
+    (params, registry, mut rng): (Res<Parameters>, Res<Registry>, ResMut<SimRng>),
+    (mut grid, mut species, mut signals, mut scent): (
+        ResMut<Grid>,
+        ResMut<Species>,
+        ResMut<Signals>,
+        ResMut<ScentField>,
+    ),
+    (mut ledger, mut genealogy, mut metrics, mut heatmaps): (
+        ResMut<EnergyLedger>,
+        ResMut<Genealogy>,
+        ResMut<Metrics>,
+        ResMut<Heatmaps>,
+    ),
+    mut orgs_query: Query<SimulatedOrganism>,
+    pellets_query: Query<(Entity, &Coord<isize>), PelletFilter>,
 ) {
     if !sim_state.paused && !sim_state.reset && sim_time.timer.tick(time.delta()).just_finished() {
-        let mut rng = rand::rng();
-        let mut children = Vec::<(Organism, Coord<isize>)>::new();
+        let rng = &mut rng.0;
+        let mut children = Vec::<(Organism, Coord<isize>, usize)>::new();
         let mut pellets_to_remove = Vec::<Coord<isize>>::new();
+        let mut interactions = Vec::<(Entity, Entity, Arc<dyn Effector>, usize)>::new();
+
+        sim_state.tick += 1;
+        environment.update(sim_state.epoch, sim_state.tick);
+        signals.swap();
+        scent.update(&grid, &params);
+
+        let genomes: HashMap<Entity, Genome> = match params.kin_distance {
+            Some(_) => orgs_query
+                .iter()
+                .map(|(e, org, ..)| (e, org.genome.clone()))
+                .collect(),
+            None => HashMap::new(),
+        };
 
-        for (e, mut org, ss, mut ns, mut coord, mut curr_dir, mut transform) in
-            orgs_query.iter_mut()
+        for (
+            e,
+            mut org,
+            body,
+            ss,
+            mut ns,
+            digestion,
+            mut coord,
+            mut curr_dir,
+            mut transform,
+            mut proprioception,
+            mut metabolism,
+        ) in orgs_query.iter_mut()
         {
             //organism death
-            if org.energy.is_sign_negative() {
-                grid.set(coord.x as usize, coord.y as usize, CellType::Empty);
+            if org.is_dead() {
+                grid.set_footprint(*coord, body.size, CellType::Empty);
                 species.decrement_species(org.species);
+                ledger.record("death", -org.energy);
+                genealogy.record(&org, sim_state.tick, "starvation");
+                metrics.death("starvation");
+                heatmaps.record(HeatmapLayer::Deaths, *coord);
 
                 commands.entity(e).despawn();
                 continue;
             }
 
-            let sensors_out = ss.process_data(&grid, *coord, *curr_dir);
-            let action = ns.get_action(sensors_out);
-            org.sub_energy(NeuralSystem::ENERGY_COST); // thinking requires energy
-
-            if action == Action::Halt {
-                continue;
-            }
-
-            let dir = action.get_dir(*curr_dir);
-            *curr_dir = dir;
-            let dir_coord: Coord<isize> = dir.value();
-            let next_coord = coord.to_owned() + dir_coord;
-
-            //world bounds check
-            if next_coord.x < 0
-                || next_coord.x >= params.grid_size as isize
-                || next_coord.y < 0
-                || next_coord.y >= params.grid_size as isize
-            {
-                continue;
+            let inputs = registry.sense(&SenseContext {
+                entity: e,
+                org: &org,
+                body,
+                ss,
+                proprioception: &proprioception,
+                coord: *coord,
+                dir: *curr_dir,
+                grid: &grid,
+                environment: &environment,
+                signals: &signals,
+                scent: &scent,
+                params: &params,
+                tick: sim_state.tick,
+                genomes: &genomes,
+            });
+            let (action, values) = ns.get_action(inputs, registry.n_actions(), rng);
+            proprioception.last_action = action;
+            metrics.action(action);
+            proprioception.blocked = false;
+            //bigger brains, genomes and senses require more energy
+            for (cause, cost) in metabolism.basal_costs() {
+                org.sub_energy(cost);
+                metabolism.record(cause, cost);
+                ledger.record(cause, -cost);
             }
 
-            //collision check
-            if grid.get(next_coord.x as usize, next_coord.y as usize) == CellType::Impassable {
-                continue;
-            } else if grid.get(next_coord.x as usize, next_coord.y as usize) == CellType::Consumable
-            {
-                org.add_energy(PELLET_ENERGY); // consuming pellet gives energy
-                org.energy = org.energy.clamp(-1., 1.);
+            let (effector, effector_action, values) = registry.resolve(action, &values);
+            let prev_coord = *coord;
+            let mut ctx = ActContext {
+                entity: e,
+                org: &mut org,
+                body,
+                digestion,
+                coord: &mut coord,
+                dir: &mut curr_dir,
+                proprioception: &mut proprioception,
+                metabolism: &mut metabolism,
+                grid: &mut grid,
+                signals: &mut signals,
+                scent: &mut scent,
+                params: &params,
+                ledger: &mut ledger,
+                eaten: &mut pellets_to_remove,
+                target: None,
+                rng,
+            };
+            effector.act(effector_action, values, &mut ctx);
 
-                pellets_to_remove.push(next_coord);
+            //interactions are resolved once every organism has acted
+            if let Some(target) = ctx.target {
+                interactions.push((e, target, effector.clone(), effector_action));
             }
 
-            org.sub_energy(1e-4); // movement takes energy
+            heatmaps.sample(*coord, org.energy, org.species);
 
-            transform.translation.x = next_coord.x as f32 * params.cell_width;
-            transform.translation.y = next_coord.y as f32 * params.cell_height;
-
-            grid.set(coord.x as usize, coord.y as usize, CellType::Empty);
-            grid.set(
-                next_coord.x as usize,
-                next_coord.y as usize,
-                CellType::Impassable,
-            );
+            //organisms replicate after moving
+            if *coord == prev_coord {
+                continue;
+            }
+            heatmaps.record(HeatmapLayer::Traffic, *coord);
 
-            *coord = next_coord;
+            transform.translation.x = (coord.x as f32 + body.offset()) * params.cell_width;
+            transform.translation.y = (coord.y as f32 + body.offset()) * params.cell_height;
 
             if org.can_replicate() {
-                let mut child = org.replicate(
+                let child = org.replicate(
+                    genealogy.next_id(),
+                    sim_state.tick,
                     params.mutate_gene_proba,
                     params.insert_gene_proba,
                     params.delete_gene_proba,
+                    rng,
                 );
-                if child.genome.get_distance(&org.genome) > 1e-1 {
-                    child.species = species.add_species();
-                }
                 species.increment_species(child.species);
-                children.push((child, *coord));
+                children.push((child, *coord, body.size));
+            }
+        }
+
+        let mut killed = HashSet::<Entity>::new();
+        for (actor, target, effector, action) in interactions.into_iter() {
+            if killed.contains(&actor) || killed.contains(&target) {
+                continue;
+            }
+            let Ok(
+                [
+                    (_, mut actor_org, ..),
+                    (_, mut target_org, target_body, _, _, _, target_coord, ..),
+                ],
+            ) = orgs_query.get_many_mut([actor, target])
+            else {
+                continue;
+            };
+            //starved organisms are already removed
+            if target_org.is_dead() {
+                continue;
+            }
+
+            let before = actor_org.energy + target_org.energy;
+            effector.interact(action, &mut actor_org, &mut target_org, &params);
+            ledger.record(
+                effector.name(),
+                actor_org.energy + target_org.energy - before,
+            );
+
+            //organism killed
+            if target_org.is_dead() {
+                killed.insert(target);
+                species.decrement_species(target_org.species);
+                ledger.record("death", -target_org.energy);
+                genealogy.record(&target_org, sim_state.tick, "predation");
+                metrics.death("predation");
+                heatmaps.record(HeatmapLayer::Deaths, *target_coord);
+                commands.entity(target).despawn();
+
+                //bigger bodies leave food on every cell they covered
+                if let Some(kind) = params.corpse_food {
+                    for coord in grid
+                        .footprint(*target_coord, target_body.size)
+                        .unwrap_or_default()
+                    {
+                        grid.set(
+                            coord.x as usize,
+                            coord.y as usize,
+                            CellType::Consumable(kind),
+                        );
+                        ledger.record("corpse", params.food[kind].energy);
+                        spawn_pellet(
+                            &mut commands,
+                            &mut meshes,
+                            &mut materials,
+                            &coord,
+                            kind,
+                            &params,
+                        );
+                    }
+                } else {
+                    grid.set_footprint(*target_coord, target_body.size, CellType::Empty);
+                }
             }
         }
 
         while let Some(pellet_coord) = pellets_to_remove.pop() {
+            heatmaps.record(HeatmapLayer::Consumption, pellet_coord);
             for (e, coord) in pellets_query.iter() {
                 if *coord == pellet_coord {
                     commands.entity(e).despawn();
@@ -111,30 +249,30 @@ pub fn sim_step_system(
             }
         }
 
-        for (child, parent_coord) in children.into_iter() {
-            let nearby_coords = grid.clone().search_area(parent_coord, 1, CellType::Empty);
+        for (child, parent_coord, parent_size) in children.into_iter() {
+            let size = decode_body(&child, &params).size;
+            let nearby_coords = grid.find_space(parent_coord, parent_size.max(size), size);
 
             if nearby_coords.len() == 0 {
+                ledger.record("replication", -child.energy);
+                genealogy.record(&child, sim_state.tick, "stillbirth");
+                metrics.death("stillbirth");
                 continue;
             }
 
             let child_coord = nearby_coords[rng.random_range(0..nearby_coords.len())];
-
-            grid.set(
-                child_coord.x as usize,
-                child_coord.y as usize,
-                CellType::Impassable,
-            );
+            metrics.birth();
 
             spawn_organism(
                 &mut commands,
-                &mut meshes,
-                &mut materials,
-                &child,
-                &child_coord,
-                species.get_color(child.species),
-                &params,
+                (&mut meshes, &mut materials),
+                &mut grid,
+                (&child, &child_coord, species.get_color(child.species)),
+                (&params, &registry),
+                rng,
             );
         }
+
+        heatmaps.advance(params.heatmap_window);
     }
 }
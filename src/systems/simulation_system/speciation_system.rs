@@ -0,0 +1,55 @@
+use crate::components::{Body, CellType, Coord, Organism};
+use crate::resources::*;
+use bevy::prelude::*;
+
+/**Organism with what changes when it changes species: its cells on the grid and its colour*/
+type SpeciatedOrganism<'a> = (
+    Entity,
+    &'a mut Organism,
+    &'a Body,
+    &'a Coord<isize>,
+    &'a MeshMaterial2d<ColorMaterial>,
+);
+
+/**Re-assign organisms to species once every epoch*/
+pub fn speciation_system(
+    params: Res<Parameters>,
+    sim_state: Res<SimState>,
+    mut last_epoch: Local<usize>,
+    (mut grid, mut species, mut phylogeny): (ResMut<Grid>, ResMut<Species>, ResMut<Phylogeny>),
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<SimRng>,
+    mut orgs_query: Query<SpeciatedOrganism>,
+) {
+    if sim_state.epoch == *last_epoch {
+        return;
+    }
+    *last_epoch = sim_state.epoch;
+
+    let (genomes, current): (Vec<_>, Vec<_>) = orgs_query
+        .iter()
+        .map(|(_, org, ..)| (org.genome.clone(), org.species))
+        .unzip();
+    let assigned = species.speciate(
+        &genomes.iter().collect::<Vec<_>>(),
+        &current,
+        params.target_species,
+        params.threshold_step,
+        &mut rng.0,
+    );
+
+    for ((e, mut org, body, coord, material), new_species) in orgs_query.iter_mut().zip(assigned) {
+        if org.species == new_species {
+            continue;
+        }
+
+        //first member of a new species records where it branched off
+        phylogeny.add(new_species, Some(org.species), sim_state.epoch, &org.genome);
+        org.species = new_species;
+
+        grid.set_footprint(*coord, body.size, CellType::Occupied(e, new_species));
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.color = species.get_color(new_species);
+        }
+    }
+}
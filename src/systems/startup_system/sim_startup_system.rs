@@ -1,77 +1,112 @@
 use super::*;
+use crate::components::ui::Follow;
 use bevy::window::PrimaryWindow;
 use bevy::color::palettes::css::MIDNIGHT_BLUE;
 
+/**Set up the world from the Parameters resource if one was inserted, otherwise from the config file*/
 pub fn sim_startup_system(
     mut window_query: Query<&mut Window, With<PrimaryWindow>>,
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
+    (mut meshes, mut materials): (ResMut<Assets<Mesh>>, ResMut<Assets<ColorMaterial>>),
+    mut images: ResMut<Assets<Image>>,
+    (registry, mut rng): (Res<Registry>, ResMut<SimRng>),
+    (mut sim_time, mut epoch_time): (ResMut<SimTime>, ResMut<EpochTime>),
+    params: Option<Res<Parameters>>,
 ) {
-    let mut window = window_query.single_mut().unwrap();
-    window.resizable = false;
+    let rng = &mut rng.0;
+    let mut params = match params {
+        Some(params) => params.clone(),
+        None => Parameters::load(CONFIG_PATH).unwrap_or_else(|e| {
+            warn!("Failed to load {}, using default parameters: {}", CONFIG_PATH, e);
+            Parameters::default()
+        }),
+    };
 
-    commands.spawn((
-        Camera2d,
-        Camera {
-            clear_color: ClearColorConfig::Custom(Color::Srgba(MIDNIGHT_BLUE)),
-            ..default()
-        },
-    ));
+    //headless runs have no window to fit the grid to or camera to render with
+    if let Ok(mut window) = window_query.single_mut() {
+        window.resizable = false;
 
-    let grid_size: usize = 500;
-    let cell_width = window.width() / grid_size as f32;
-    let cell_height = window.height() / grid_size as f32;
+        params.cell_width = window.width() / params.grid_width as f32;
+        params.cell_height = window.height() / params.grid_height as f32;
 
-    let params = Parameters {
-        grid_size,
-        n_initial_entities: 100,
-        n_initial_connections: 25,
-        n_initial_neurons: 10,
-        mutate_gene_proba: 0.1,
-        insert_gene_proba: 0.08,
-        delete_gene_proba: 0.05,
-        lifespan: 15,
-        cell_height,
-        cell_width,
-    };
+        commands.spawn((
+            Camera2d,
+            Camera {
+                clear_color: ClearColorConfig::Custom(Color::Srgba(MIDNIGHT_BLUE)),
+                ..default()
+            },
+            Transform::from_translation(world_rect(&params).center().extend(0.)),
+            Follow::default(),
+        ));
+    }
 
     commands.insert_resource(params.clone());
+    sim_time.set_tick_duration(&mut epoch_time, params.tick_seconds);
 
-    let (mut orgs, species, coords, mut grid) = init_system(params);
-    orgs.iter_mut().for_each(|o| {
-        o.genome.set_gene_types(
-            params.n_initial_connections,
-            params.n_initial_neurons + SensorySystem::N_SENSORS,
-        )
-    });
+    let (mut orgs, species, coords, mut grid) = init_system(&params, rng);
+    let mut genealogy = Genealogy::new(params.genealogy_log.as_deref());
+    orgs.iter_mut().for_each(|org| org.id = genealogy.next_id());
+    let food_field = FoodField::new(
+        &params.food,
+        (params.grid_width, params.grid_height),
+        rng,
+    );
+    let environment = Environment::new(&params.schedule, params.max_sensor_range);
 
     for (org, coord) in orgs.iter().zip(coords.iter()) {
         spawn_organism(
             &mut commands,
-            &mut meshes,
-            &mut materials,
-            org,
-            coord,
-            species.get_color(org.species),
-            &params,
+            (&mut meshes, &mut materials),
+            &mut grid,
+            (org, coord, species.get_color(org.species)),
+            (&params, &registry),
+            rng,
         );
     }
 
     commands.insert_resource(species);
 
-    let pellet_coords = energy_system(orgs.iter().map(|org| org.energy).sum::<f32>(), &grid);
-    for coord in pellet_coords.iter() {
-        grid.set(coord.x as usize, coord.y as usize, CellType::Consumable);
+    let pellet_coords = energy_system(
+        orgs.iter().map(|org| org.energy).sum::<f32>(),
+        &grid,
+        &food_field,
+        &environment,
+        &params,
+        rng,
+    );
+    for (coord, kind) in pellet_coords.iter() {
+        grid.set(coord.x as usize, coord.y as usize, CellType::Consumable(*kind));
         spawn_pellet(
             &mut commands,
             &mut meshes,
             &mut materials,
             coord,
-            params.cell_width,
-            params.cell_height,
+            *kind,
+            &params,
         );
     }
 
     commands.insert_resource(grid.clone());
+    commands.insert_resource(food_field);
+    commands.insert_resource(environment);
+    commands.insert_resource(ScentField::new(
+        params.food.len(),
+        (params.grid_width, params.grid_height),
+        params.topology,
+    ));
+
+    spawn_scent_overlay(&mut commands, &mut images, &params);
+    let heatmaps = Heatmaps::new(&params);
+    spawn_heatmap_overlay(&mut commands, &mut images, &heatmaps, &params);
+    commands.insert_resource(heatmaps);
+    commands.insert_resource(Signals::new((params.grid_width, params.grid_height)));
+    commands.insert_resource(genealogy);
+    commands.insert_resource(Metrics::new(
+        params.metrics_log.as_deref(),
+        params.metrics_format,
+        registry.action_names(),
+    ));
+    commands.insert_resource(Phylogeny::founders(
+        orgs.iter().map(|org| (org.species, &org.genome)),
+    ));
 }
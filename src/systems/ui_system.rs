@@ -1,11 +1,23 @@
 mod energy_info_system;
 mod epoch_info_system;
 mod game_menu;
+mod heatmap_overlay_system;
+mod ledger_info_system;
+mod minimap_system;
+mod parameter_panel_system;
+mod phylogeny_view_system;
 mod population_info_system;
+mod scent_overlay_system;
 mod species_info_system;
 
 pub use energy_info_system::*;
 pub use epoch_info_system::*;
 pub use game_menu::*;
+pub use heatmap_overlay_system::*;
+pub use ledger_info_system::*;
+pub use minimap_system::*;
+pub use parameter_panel_system::*;
+pub use phylogeny_view_system::*;
 pub use population_info_system::*;
+pub use scent_overlay_system::*;
 pub use species_info_system::*;
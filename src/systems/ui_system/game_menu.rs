@@ -1,10 +1,18 @@
 use crate::components::ui::*;
 use bevy::prelude::*;
-use bevy::color::palettes::css::WHITE;
+use bevy::ui::RelativeCursorPosition;
+use bevy::color::palettes::css::{MIDNIGHT_BLUE, WHITE};
+
+pub const MINIMAP_WIDTH: f32 = 200.0;
 
 pub fn build_game_menu(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
-    //TODO: add more ui elements
     let control_panel = build_control_panel(commands, &asset_server);
+    let phylogeny_panel = build_phylogeny_panel(commands, asset_server);
+    let parameter_panel = build_parameter_panel(commands, asset_server);
+    let minimap = build_minimap(commands);
+    commands
+        .entity(control_panel)
+        .add_children(&[phylogeny_panel, parameter_panel, minimap]);
     control_panel
 }
 
@@ -84,6 +92,30 @@ fn build_info_panel(commands: &mut Commands, asset_server: &Res<AssetServer>) ->
         ))
         .id();
 
+    let ledger = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                font_size: 16.0,
+                ..default()
+            },
+            LedgerText,
+        ))
+        .id();
+
+    let heatmap = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                font_size: 16.0,
+                ..default()
+            },
+            HeatmapText,
+        ))
+        .id();
+
     commands
         .spawn(Node {
             width: Val::Percent(50.0),
@@ -91,7 +123,7 @@ fn build_info_panel(commands: &mut Commands, asset_server: &Res<AssetServer>) ->
             flex_direction: FlexDirection::Column,
             ..default()
         })
-        .add_children(&[info_label, epoch, population, energy])
+        .add_children(&[info_label, epoch, population, energy, ledger, heatmap])
         .id()
 }
 
@@ -128,6 +160,201 @@ fn build_species_panel(commands: &mut Commands, asset_server: &Res<AssetServer>)
         .id()
 }
 
+fn build_phylogeny_panel(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+    let tree = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                font_size: 12.0,
+                ..default()
+            },
+            PhylogenyText,
+        ))
+        .id();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.),
+                right: Val::Px(0.),
+                width: Val::Percent(25.0),
+                max_height: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(5.)),
+                flex_direction: FlexDirection::Column,
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.6)),
+            Visibility::Hidden,
+            PhylogenyPanel,
+        ))
+        .add_children(&[tree])
+        .id()
+}
+
+/**Grid texture in the top right corner, its height is fitted to the grid once the texture exists*/
+fn build_minimap(commands: &mut Commands) -> Entity {
+    let viewport = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BorderColor::all(WHITE),
+            MinimapViewport,
+        ))
+        .id();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                width: Val::Px(MINIMAP_WIDTH),
+                height: Val::Px(MINIMAP_WIDTH),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(MIDNIGHT_BLUE.into()),
+            ImageNode::default(),
+            Interaction::default(),
+            RelativeCursorPosition::default(),
+            Minimap,
+        ))
+        .add_children(&[viewport])
+        .id()
+}
+
+fn build_parameter_panel(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
+    let mut rows: Vec<Entity> = Setting::ALL
+        .iter()
+        .map(|setting| spawn_setting_row(commands, asset_server, *setting))
+        .collect();
+
+    let buttons = [(PanelButton::Reset, "Reset"), (PanelButton::Save, "Save")]
+        .map(|(button, label)| spawn_panel_button(commands, asset_server, button, label));
+    rows.push(
+        commands
+            .spawn(Node {
+                justify_content: JustifyContent::SpaceEvenly,
+                margin: UiRect::top(Val::Px(5.)),
+                ..default()
+            })
+            .add_children(&buttons)
+            .id(),
+    );
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.),
+                left: Val::Px(0.),
+                padding: UiRect::all(Val::Px(5.)),
+                row_gap: Val::Px(3.),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0., 0., 0., 0.6)),
+            Visibility::Hidden,
+            ParameterPanel,
+        ))
+        .add_children(&rows)
+        .id()
+}
+
+fn spawn_setting_row(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    setting: Setting,
+) -> Entity {
+    let text = commands
+        .spawn((
+            Text::new(""),
+            TextFont {
+                font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                font_size: 14.0,
+                ..default()
+            },
+            Node {
+                width: Val::Px(200.),
+                ..default()
+            },
+            SettingText(setting),
+        ))
+        .id();
+
+    let fill = commands
+        .spawn((
+            Node {
+                width: Val::Percent(0.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            BackgroundColor(WHITE.into()),
+            SettingFill(setting),
+        ))
+        .id();
+
+    let slider = commands
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(120.),
+                height: Val::Px(12.),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.3, 0.3, 0.3)),
+            RelativeCursorPosition::default(),
+            SettingSlider(setting),
+        ))
+        .add_child(fill)
+        .id();
+
+    commands
+        .spawn(Node {
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .add_children(&[text, slider])
+        .id()
+}
+
+fn spawn_panel_button(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    button: PanelButton,
+    label: &str,
+) -> Entity {
+    let text = commands
+        .spawn((
+            Text::new(label),
+            TextFont {
+                font: asset_server.load("fonts/OpenSans-Regular.ttf"),
+                font_size: 16.0,
+                ..default()
+            },
+        ))
+        .id();
+
+    commands
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(10.), Val::Px(2.)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.3, 0.3, 0.3)),
+            button,
+        ))
+        .add_child(text)
+        .id()
+}
+
 fn spawn_species_label(commands: &mut Commands, asset_server: &Res<AssetServer>) -> Entity {
     commands
         .spawn((
@@ -138,7 +365,8 @@ fn spawn_species_label(commands: &mut Commands, asset_server: &Res<AssetServer>)
                 ..default()
             },
             TextColor(WHITE.into()),
-            SpeciesText,
+            SpeciesText::default(),
+            Interaction::default(),
         ))
         .id()
 }
@@ -0,0 +1,70 @@
+use crate::components::ui::*;
+use crate::resources::*;
+use bevy::prelude::*;
+
+/**Colour of the regions with the highest value, lower values fade through yellow to transparent*/
+const HOT_COLOR: (u8, u8, u8) = (255, 40, 0);
+
+/**Paint the selected heatmap layer onto the overlay texture, values are relative to the highest region*/
+pub fn heatmap_overlay_system(
+    heatmaps: Res<Heatmaps>,
+    species: Res<Species>,
+    overlay: Single<(&Sprite, &Visibility, &HeatmapOverlay)>,
+    mut heatmap_text: Single<&mut Text, With<HeatmapText>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let (sprite, visibility, overlay) = *overlay;
+    let Some(layer) = overlay.0 else {
+        heatmap_text.0.clear();
+        return;
+    };
+    heatmap_text.0 = format!("Heatmap: {}", layer.label());
+    if visibility == Visibility::Hidden {
+        return;
+    }
+    let Some(image) = images.get_mut(&sprite.image) else {
+        return;
+    };
+    let Some(data) = image.data.as_mut() else {
+        return;
+    };
+
+    let (width, height) = heatmaps.shape();
+    let values: Vec<f32> = match layer {
+        HeatmapLayer::Territory => Vec::new(),
+        _ => (0..width * height)
+            .map(|i| heatmaps.value(layer, i))
+            .collect(),
+    };
+    let max = values.iter().copied().fold(0., f32::max);
+
+    for (i, pixel) in data.chunks_exact_mut(4).enumerate().take(width * height) {
+        let region = i % width + (height - 1 - i / width) * width;
+
+        let color = match layer {
+            HeatmapLayer::Territory => match heatmaps.dominant(region) {
+                Some((s, share)) => {
+                    let color = species.get_color(s).to_srgba();
+                    (
+                        (color.red * 255.) as u8,
+                        (color.green * 255.) as u8,
+                        (color.blue * 255.) as u8,
+                        (share * 200.) as u8,
+                    )
+                }
+                None => (0, 0, 0, 0),
+            },
+            _ if max > 0. => {
+                let v = values[region] / max;
+                (
+                    HOT_COLOR.0,
+                    (255. - (255. - HOT_COLOR.1 as f32) * v) as u8,
+                    HOT_COLOR.2,
+                    (v.sqrt() * 200.) as u8,
+                )
+            }
+            _ => (0, 0, 0, 0),
+        };
+        pixel.copy_from_slice(&[color.0, color.1, color.2, color.3]);
+    }
+}
@@ -0,0 +1,15 @@
+use crate::components::ui::*;
+use crate::resources::*;
+use bevy::prelude::*;
+
+pub fn ledger_info_system(
+    ledger: Res<EnergyLedger>,
+    mut ledger_text: Single<&mut Text, With<LedgerText>>,
+) {
+    ledger_text.0 = format!(
+        "In {:+.2} Out {:+.2} Anomalies {}",
+        ledger.inflow(),
+        ledger.outflow(),
+        ledger.n_anomalies
+    );
+}
@@ -0,0 +1,78 @@
+use crate::components::CellType;
+use crate::components::ui::*;
+use crate::resources::*;
+use crate::systems::{MINIMAP_WIDTH, blank_image, world_rect};
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use bevy::window::PrimaryWindow;
+
+const IMPASSABLE_COLOR: [u8; 4] = [128, 128, 128, 255];
+
+/**Move the camera to the point pressed on the minimap, repaint the grid when it changes and frame the camera's view*/
+pub fn minimap_system(
+    (grid, params, species): (Res<Grid>, Res<Parameters>, Res<Species>),
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&mut Transform, &mut Follow), With<Camera>>,
+    minimap: Single<
+        (
+            &mut Node,
+            &mut ImageNode,
+            &Interaction,
+            &RelativeCursorPosition,
+        ),
+        With<Minimap>,
+    >,
+    mut viewport: Single<&mut Node, (With<MinimapViewport>, Without<Minimap>)>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let (mut node, mut image_node, interaction, cursor) = minimap.into_inner();
+    let (mut transform, mut follow) = camera.into_inner();
+    let world = world_rect(&params);
+
+    //normalized positions are centred on the node with y pointing down
+    if *interaction == Interaction::Pressed
+        && let Some(position) = cursor.normalized
+    {
+        follow.clear();
+        transform.translation.x = world.min.x + (position.x + 0.5) * world.width();
+        transform.translation.y = world.min.y + (0.5 - position.y) * world.height();
+    }
+
+    let (width, height) = (grid.width(), grid.height());
+    let created = images
+        .get(&image_node.image)
+        .is_none_or(|image| image.size() != UVec2::new(width as u32, height as u32));
+    if created {
+        image_node.image = images.add(blank_image((width, height)));
+        node.height = Val::Px(MINIMAP_WIDTH * height as f32 / width as f32);
+    }
+
+    if (created || grid.is_changed())
+        && let Some(data) = images
+            .get_mut(&image_node.image)
+            .and_then(|image| image.data.as_mut())
+    {
+        for (i, pixel) in data.chunks_exact_mut(4).enumerate().take(width * height) {
+            let (x, y) = (i % width, height - 1 - i / width);
+            let color = match grid.get(x, y) {
+                CellType::Empty => [0, 0, 0, 0],
+                CellType::Impassable => IMPASSABLE_COLOR,
+                CellType::Consumable(kind) => {
+                    let color = params.food[kind].color;
+                    [color.0, color.1, color.2, 255]
+                }
+                CellType::Occupied(_, s) => species.get_color(s).to_srgba().to_u8_array(),
+            };
+            pixel.copy_from_slice(&color);
+        }
+    }
+
+    let view = Rect::from_center_size(
+        transform.translation.truncate(),
+        window.size() * transform.scale.truncate(),
+    );
+    viewport.left = Val::Percent((view.min.x - world.min.x) / world.width() * 100.);
+    viewport.top = Val::Percent((world.max.y - view.max.y) / world.height() * 100.);
+    viewport.width = Val::Percent(view.width() / world.width() * 100.);
+    viewport.height = Val::Percent(view.height() / world.height() * 100.);
+}
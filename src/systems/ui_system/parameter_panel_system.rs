@@ -0,0 +1,66 @@
+use crate::components::ui::*;
+use crate::resources::*;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+
+pub fn parameter_panel_system(
+    (mut params, mut sim_state): (ResMut<Parameters>, ResMut<SimState>),
+    (mut sim_time, mut epoch_time): (ResMut<SimTime>, ResMut<EpochTime>),
+    panel: Single<&Visibility, With<ParameterPanel>>,
+    sliders_query: Query<(&Interaction, &RelativeCursorPosition, &SettingSlider)>,
+    buttons_query: Query<(&Interaction, &PanelButton), Changed<Interaction>>,
+    mut fills_query: Query<(&mut Node, &SettingFill)>,
+    mut texts_query: Query<(&mut Text, &SettingText)>,
+) {
+    if **panel == Visibility::Hidden {
+        return;
+    }
+
+    for (interaction, cursor, slider) in sliders_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(position) = cursor.normalized else {
+            continue;
+        };
+
+        //normalized positions are centred on the node
+        let value = slider.0.value_at(position.x + 0.5);
+        if value != slider.0.get(&params, &sim_time) {
+            slider
+                .0
+                .set(value, &mut params, &mut sim_time, &mut epoch_time);
+        }
+    }
+
+    for (interaction, button) in buttons_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match button {
+            PanelButton::Reset => sim_state.reset = true,
+            PanelButton::Save => {
+                params.tick_seconds = sim_time.timer.duration().as_secs_f32();
+                match params.save(CONFIG_PATH) {
+                    Ok(()) => info!("Saved parameters to {}", CONFIG_PATH),
+                    Err(e) => warn!("Failed to save parameters to {}: {}", CONFIG_PATH, e),
+                }
+            }
+        }
+    }
+
+    for (mut node, fill) in fills_query.iter_mut() {
+        let fraction = fill.0.fraction_of(fill.0.get(&params, &sim_time));
+        node.width = Val::Percent(fraction * 100.);
+    }
+
+    for (mut text, setting) in texts_query.iter_mut() {
+        let value = setting.0.get(&params, &sim_time);
+        text.0 = match setting.0 {
+            Setting::Lifespan => format!("{}: {}", setting.0.label(), value),
+            _ if value < 1e-2 => format!("{}: {:.1e}", setting.0.label(), value),
+            _ => format!("{}: {:.3}", setting.0.label(), value),
+        };
+    }
+}
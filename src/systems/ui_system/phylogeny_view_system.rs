@@ -0,0 +1,22 @@
+use crate::components::ui::*;
+use crate::resources::*;
+use bevy::prelude::*;
+
+const MAX_LINES: usize = 40;
+
+pub fn phylogeny_view_system(
+    phylogeny: Res<Phylogeny>,
+    panel: Single<&Visibility, With<PhylogenyPanel>>,
+    mut tree_text: Single<&mut Text, With<PhylogenyText>>,
+) {
+    if **panel == Visibility::Hidden || !phylogeny.is_changed() {
+        return;
+    }
+
+    tree_text.0 = format!(
+        "Phylogeny: {} species, {} extinct\n{}",
+        phylogeny.n_species(),
+        phylogeny.n_extinct(),
+        phylogeny.outline(MAX_LINES)
+    );
+}
@@ -0,0 +1,39 @@
+use crate::components::ui::*;
+use crate::resources::*;
+use bevy::prelude::*;
+
+const DEPOSIT_COLOR: (u8, u8, u8) = (255, 0, 255);
+
+/**Paint the strongest scent channel of every cell onto the overlay texture*/
+pub fn scent_overlay_system(
+    scent: Res<ScentField>,
+    params: Res<Parameters>,
+    overlay: Single<(&Sprite, &Visibility), With<ScentOverlay>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let (sprite, visibility) = *overlay;
+    if visibility == Visibility::Hidden {
+        return;
+    }
+    let Some(image) = images.get_mut(&sprite.image) else {
+        return;
+    };
+    let Some(data) = image.data.as_mut() else {
+        return;
+    };
+
+    let (width, height) = (params.grid_width, params.grid_height);
+    for (i, pixel) in data.chunks_exact_mut(4).enumerate().take(width * height) {
+        let (x, y) = (i % width, height - 1 - i / width);
+        let (channel, value) = (0..scent.n_channels())
+            .map(|c| (c, scent.get(c, x, y)))
+            .fold((0, 0.), |acc, v| if v.1 > acc.1 { v } else { acc });
+
+        let color = if channel == 0 {
+            DEPOSIT_COLOR
+        } else {
+            params.food[channel - 1].color
+        };
+        pixel.copy_from_slice(&[color.0, color.1, color.2, (value.tanh() * 200.) as u8]);
+    }
+}